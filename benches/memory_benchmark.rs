@@ -0,0 +1,146 @@
+//! Memory used by the walk, measured with a counting allocator.
+//!
+//! Generates a tree of 100 directories with 1000 files each and reports the
+//! number of allocations per entry and the heap memory retained by the
+//! collected entries. Run with `cargo bench --bench memory_benchmark`.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::fs::{create_dir_all, File};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use jwalk_meta::{DirEntry, Parallelism, WalkDir};
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        let allocated = ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+        PEAK.fetch_max(allocated, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        ALLOCATED.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        if new_size > layout.size() {
+            let grown = new_size - layout.size();
+            let allocated = ALLOCATED.fetch_add(grown, Ordering::Relaxed) + grown;
+            PEAK.fetch_max(allocated, Ordering::Relaxed);
+        } else {
+            ALLOCATED.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const DIRS: usize = 100;
+const FILES_PER_DIR: usize = 1000;
+
+fn create_tree(root: &Path) {
+    for dir_index in 0..DIRS {
+        let dir = root.join(format!("directory_{:03}", dir_index));
+        create_dir_all(&dir).unwrap();
+        for file_index in 0..FILES_PER_DIR {
+            File::create(dir.join(format!("file_{:04}.rs", file_index))).unwrap();
+        }
+    }
+}
+
+struct Measurement {
+    entries: usize,
+    allocations: usize,
+    retained: usize,
+    peak: usize,
+}
+
+fn measure<T>(walk: impl FnOnce() -> (usize, T)) -> Measurement {
+    let allocations_before = ALLOCATIONS.load(Ordering::SeqCst);
+    let allocated_before = ALLOCATED.load(Ordering::SeqCst);
+    PEAK.store(allocated_before, Ordering::SeqCst);
+    let (entries, kept) = walk();
+    let measurement = Measurement {
+        entries,
+        allocations: ALLOCATIONS.load(Ordering::SeqCst) - allocations_before,
+        retained: ALLOCATED
+            .load(Ordering::SeqCst)
+            .saturating_sub(allocated_before),
+        peak: PEAK.load(Ordering::SeqCst) - allocated_before,
+    };
+    drop(kept);
+    measurement
+}
+
+fn report(name: &str, measurement: Measurement) {
+    let entries = measurement.entries.max(1);
+    println!(
+        "{:<28} {:>8} entries {:>8.2} allocs/entry {:>8.1} B/entry retained {:>8.1} B/entry peak",
+        name,
+        measurement.entries,
+        measurement.allocations as f64 / entries as f64,
+        measurement.retained as f64 / entries as f64,
+        measurement.peak as f64 / entries as f64,
+    );
+}
+
+fn main() {
+    // `cargo bench` passes `--bench`, `cargo test` runs benches with no
+    // arguments and only checks that they build.
+    if !std::env::args().any(|arg| arg == "--bench") {
+        return;
+    }
+
+    let tmp = tempfile::tempdir().unwrap();
+    create_tree(tmp.path());
+    let root = tmp.path();
+
+    for (name, parallelism) in [
+        ("serial", Parallelism::Serial),
+        ("rayon", Parallelism::RayonNewPool(4)),
+    ] {
+        report(
+            &format!("{}, streamed", name),
+            measure(|| {
+                let count = WalkDir::new(root)
+                    .parallelism(parallelism.clone())
+                    .into_iter()
+                    .count();
+                (count, ())
+            }),
+        );
+        report(
+            &format!("{}, collected", name),
+            measure(|| {
+                let entries: Vec<DirEntry<((), ())>> = WalkDir::new(root)
+                    .parallelism(parallelism.clone())
+                    .into_iter()
+                    .map(|dir_entry| dir_entry.unwrap())
+                    .collect();
+                (entries.len(), entries)
+            }),
+        );
+        report(
+            &format!("{}, path() per entry", name),
+            measure(|| {
+                let mut count = 0;
+                for dir_entry in WalkDir::new(root).parallelism(parallelism.clone()) {
+                    std::hint::black_box(dir_entry.unwrap().path());
+                    count += 1;
+                }
+                (count, ())
+            }),
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Walks of synthetic trees stressing the ordering of parallel results: a
+//! deep tree, where index paths are long, and a wide tree, where many
+//! directories are queued at once.
+
+use std::fs::{create_dir_all, File};
+use std::path::{Path, PathBuf};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use tempfile::TempDir;
+
+use jwalk_meta::{Parallelism, WalkDir};
+
+// 8 chains of 200 nested directories with a file in each directory.
+fn create_deep_tree(root: &Path) {
+    for chain in 0..8 {
+        let mut dir = root.join(format!("chain{}", chain));
+        for level in 0..200 {
+            dir.push(format!("level{:03}", level));
+        }
+        create_dir_all(&dir).unwrap();
+        let mut dir = dir.as_path();
+        while dir != root {
+            File::create(dir.join("file.txt")).unwrap();
+            dir = dir.parent().unwrap();
+        }
+    }
+}
+
+// 4 levels of 10 directories each, 10 files in every directory.
+fn create_wide_tree(root: &Path) {
+    fn create(dir: &Path, depth: usize) {
+        create_dir_all(dir).unwrap();
+        for file in 0..10 {
+            File::create(dir.join(format!("file{:02}.txt", file))).unwrap();
+        }
+        if depth < 4 {
+            for sub_dir in 0..10 {
+                create(&dir.join(format!("dir{:02}", sub_dir)), depth + 1);
+            }
+        }
+    }
+    create(root, 0);
+}
+
+fn walk(root: &Path, parallelism: Parallelism) -> usize {
+    WalkDir::new(root)
+        .sort(true)
+        .parallelism(parallelism)
+        .into_iter()
+        .count()
+}
+
+fn tree_shape_benches(c: &mut Criterion) {
+    let deep = TempDir::new().unwrap();
+    create_deep_tree(deep.path());
+    let wide = TempDir::new().unwrap();
+    create_wide_tree(wide.path());
+    let trees: [(&str, PathBuf); 2] = [
+        ("deep", deep.path().to_path_buf()),
+        ("wide", wide.path().to_path_buf()),
+    ];
+
+    for (name, root) in trees.iter() {
+        c.bench_function(&format!("jwalk {} (sorted, n threads)", name), |b| {
+            b.iter(|| black_box(walk(root, Parallelism::RayonNewPool(0))))
+        });
+        c.bench_function(&format!("jwalk {} (sorted, 1 thread)", name), |b| {
+            b.iter(|| black_box(walk(root, Parallelism::Serial)))
+        });
+    }
+}
+
+criterion_group! {
+  name = benches;
+  config = Criterion::default().sample_size(10);
+  targets = tree_shape_benches
+}
+
+criterion_main!(benches);
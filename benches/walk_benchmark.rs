@@ -12,10 +12,8 @@ use std::sync::mpsc;
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
 use flate2::read::GzDecoder;
 use ignore::WalkBuilder;
-use num_cpus;
 use rayon::prelude::*;
 use tar::Archive;
-use walkdir;
 
 use jwalk_meta::{Error, Parallelism, WalkDir, WalkDirGeneric};
 
@@ -66,7 +64,7 @@ fn download_and_unpack_linux_kernel() -> Result<(), io::Error> {
     let linux_kernel_archive = linux_kernel_archive();
     if !linux_kernel_archive.exists() {
         // Download linux kernel
-        download_linux_kernel().map_err(|e| io::Error::new(ErrorKind::Other, e.to_string()))?;
+        download_linux_kernel().map_err(|e| io::Error::other(e.to_string()))?;
     }
     let linux_dir = linux_dir();
     if !linux_dir.exists() {
@@ -91,6 +89,7 @@ fn download_and_unpack_linux_kernel() -> Result<(), io::Error> {
     Ok(())
 }
 
+#[allow(clippy::unit_arg)]
 fn walk_benches(c: &mut Criterion) {
     download_and_unpack_linux_kernel().unwrap();
 
@@ -203,7 +202,7 @@ fn walk_benches(c: &mut Criterion) {
                     })
                 });
             let mut metadatas: Vec<_> = rx.into_iter().collect();
-            metadatas.sort_by(|a, b| a.len().cmp(&b.len()))
+            metadatas.sort_by_key(|a| a.len())
         })
     });
 
@@ -226,7 +225,7 @@ fn walk_benches(c: &mut Criterion) {
                     })
                 });
             let mut metadatas: Vec<_> = rx.into_iter().collect();
-            metadatas.sort_by(|a, b| a.len().cmp(&b.len()))
+            metadatas.sort_by_key(|a| a.len())
         })
     });
 
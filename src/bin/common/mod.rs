@@ -0,0 +1,163 @@
+//! Helpers shared by the command line tools.
+
+#![allow(dead_code)]
+
+use std::fmt::Write;
+use std::process;
+
+/// Print an error message prefixed with the program name and exit with
+/// status 2, the usual exit status for usage errors.
+pub fn usage_error(program: &str, message: &str) -> ! {
+    eprintln!("{}: {}", program, message);
+    eprintln!("Try '{} --help' for more information.", program);
+    process::exit(2)
+}
+
+/// Parse the value of a numeric option.
+pub fn parse_number<T: std::str::FromStr>(program: &str, option: &str, value: &str) -> T {
+    value.parse().unwrap_or_else(|_| {
+        usage_error(
+            program,
+            &format!("invalid number for {}: '{}'", option, value),
+        )
+    })
+}
+
+/// Match `text` against a shell style glob `pattern`.
+///
+/// Supports `*` (any sequence), `?` (any single character) and bracket
+/// expressions such as `[abc]`, `[a-z]` and `[!0-9]`. A backslash escapes the
+/// following character.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position after the last `*` and the text position it is matched up to
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    backtrack = Some((p + 1, t));
+                    p += 1;
+                    continue;
+                }
+                '?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                }
+                '[' => {
+                    if let Some((matched, next)) = match_bracket(&pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    } else if text[t] == '[' {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+                '\\' if p + 1 < pattern.len() => {
+                    if pattern[p + 1] == text[t] {
+                        p += 2;
+                        t += 1;
+                        continue;
+                    }
+                }
+                c => {
+                    if c == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                }
+            }
+        }
+        match backtrack {
+            Some((star_p, star_t)) => {
+                backtrack = Some((star_p, star_t + 1));
+                p = star_p;
+                t = star_t + 1;
+            }
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+// Match `c` against the bracket expression starting at `pattern[start]`.
+// Returns whether it matched and the position after the closing bracket, or
+// `None` if the expression isn't terminated.
+fn match_bracket(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+    let mut i = start + 1;
+    let negate = matches!(pattern.get(i), Some('!') | Some('^'));
+    if negate {
+        i += 1;
+    }
+    let mut matched = false;
+    let mut first = true;
+    while i < pattern.len() {
+        if pattern[i] == ']' && !first {
+            return Some((matched != negate, i + 1));
+        }
+        first = false;
+        let low = pattern[i];
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            matched |= low <= c && c <= pattern[i + 2];
+            i += 3;
+        } else {
+            matched |= low == c;
+            i += 1;
+        }
+    }
+    None
+}
+
+/// Format a size in bytes the way `du -h` does: powers of 1024 with a single
+/// letter suffix, one decimal below 10 and values rounded up.
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 8] = ["K", "M", "G", "T", "P", "E", "Z", "Y"];
+    if bytes < 1024 {
+        return bytes.to_string();
+    }
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    value /= 1024.0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if value < 10.0 {
+        let rounded = (value * 10.0).ceil() / 10.0;
+        if rounded < 10.0 {
+            return format!("{:.1}{}", rounded, UNITS[unit]);
+        }
+    }
+    format!("{:.0}{}", value.ceil(), UNITS[unit])
+}
+
+/// Quote `s` as a JSON string.
+pub fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                let _ = write!(quoted, "\\u{:04x}", c as u32);
+            }
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
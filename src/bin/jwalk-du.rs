@@ -0,0 +1,337 @@
+//! Summarize disk usage of directories, similar to `du`.
+//!
+//! ```text
+//! jwalk-du [OPTIONS] [PATH]...
+//! ```
+//!
+//! Run `jwalk-du --help` for the list of options.
+
+use std::env;
+use std::fs;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+use jwalk_meta::{DirEntry, MetaDataExt, Parallelism, WalkDirGeneric};
+
+mod common;
+
+use common::{glob_match, human_size, json_string, parse_number, usage_error};
+
+const PROGRAM: &str = "jwalk-du";
+
+const USAGE: &str = "\
+Usage: jwalk-du [OPTIONS] [PATH]...
+
+Summarize disk usage of each PATH (default: the current directory),
+recursively for directories.
+
+Options:
+  -d, --max-depth N      print the total for a directory only if it is N or
+                         fewer levels below PATH
+  -s, --summarize        print only the total of each PATH (--max-depth 0)
+      --apparent-size    print apparent sizes instead of allocated disk usage
+  -l, --count-links      count sizes of hard linked files many times
+  -x, --one-file-system  skip directories on different file systems
+      --exclude PATTERN  skip files and directories whose name matches the
+                         glob PATTERN, may be given multiple times
+  -h, --human-readable   print sizes in human readable format (e.g. 1.5K 23M)
+  -t, --top N            print only the N biggest directories, largest first
+      --json             print the result as JSON array
+  -j, --threads N        number of threads to use (default: all cores)
+      --help             print this help and exit
+";
+
+struct Options {
+    paths: Vec<PathBuf>,
+    max_depth: usize,
+    apparent_size: bool,
+    count_links: bool,
+    one_file_system: bool,
+    excludes: Vec<String>,
+    human_readable: bool,
+    top: Option<usize>,
+    json: bool,
+    threads: Option<usize>,
+}
+
+impl Options {
+    fn parse() -> Options {
+        let mut options = Options {
+            paths: Vec::new(),
+            max_depth: usize::MAX,
+            apparent_size: false,
+            count_links: false,
+            one_file_system: false,
+            excludes: Vec::new(),
+            human_readable: false,
+            top: None,
+            json: false,
+            threads: None,
+        };
+        let mut args = env::args_os().skip(1);
+        while let Some(arg) = args.next() {
+            let arg_str = match arg.to_str() {
+                Some(arg_str) if arg_str.starts_with('-') && arg_str != "-" => arg_str.to_owned(),
+                _ => {
+                    options.paths.push(PathBuf::from(arg));
+                    continue;
+                }
+            };
+            // Accept `--option=value` as well as `--option value`
+            let (name, inline_value) = match arg_str.split_once('=') {
+                Some((name, value)) if name.starts_with("--") => {
+                    (name.to_owned(), Some(value.to_owned()))
+                }
+                _ => (arg_str, None),
+            };
+            let mut value = |name: &str| -> String {
+                inline_value.clone().unwrap_or_else(|| {
+                    args.next()
+                        .and_then(|value| value.into_string().ok())
+                        .unwrap_or_else(|| {
+                            usage_error(PROGRAM, &format!("option '{}' requires an argument", name))
+                        })
+                })
+            };
+            match name.as_str() {
+                "-d" | "--max-depth" => {
+                    options.max_depth = parse_number(PROGRAM, &name, &value(&name));
+                }
+                "-s" | "--summarize" => options.max_depth = 0,
+                "--apparent-size" => options.apparent_size = true,
+                "-l" | "--count-links" => options.count_links = true,
+                "-x" | "--one-file-system" => options.one_file_system = true,
+                "--exclude" => options.excludes.push(value(&name)),
+                "-h" | "--human-readable" => options.human_readable = true,
+                "-t" | "--top" => options.top = Some(parse_number(PROGRAM, &name, &value(&name))),
+                "--json" => options.json = true,
+                "-j" | "--threads" => {
+                    options.threads = Some(parse_number(PROGRAM, &name, &value(&name)));
+                }
+                "--help" => {
+                    print!("{}", USAGE);
+                    process::exit(0);
+                }
+                "--" => {
+                    options.paths.extend(args.by_ref().map(PathBuf::from));
+                }
+                _ => usage_error(PROGRAM, &format!("unrecognized option '{}'", name)),
+            }
+        }
+        if options.paths.is_empty() {
+            options.paths.push(PathBuf::from("."));
+        }
+        options
+    }
+
+    fn parallelism(&self) -> Parallelism {
+        match self.threads {
+            Some(1) => Parallelism::Serial,
+            Some(threads) => Parallelism::RayonNewPool(threads),
+            None => Parallelism::RayonNewPool(0),
+        }
+    }
+}
+
+// The read dir state holds the device of the PATH for `--one-file-system`
+type DuState = (Option<u64>, ());
+
+/// Size of a single entry, not including the contents of directories.
+fn entry_size(dir_entry: &DirEntry<DuState>, apparent_size: bool) -> u64 {
+    let apparent = dir_entry
+        .metadata
+        .as_ref()
+        .map_or(0, |metadata| metadata.size);
+    if apparent_size || dir_entry.file_type.is_symlink() {
+        return apparent;
+    }
+    allocated_size(dir_entry.metadata_ext.as_ref()).unwrap_or(apparent)
+}
+
+fn device(dir_entry: &DirEntry<DuState>) -> Option<u64> {
+    let (device, _) = dir_entry.metadata_ext.as_ref()?.inode_key()?;
+    Some(device)
+}
+
+#[cfg(unix)]
+fn path_device(path: &Path) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    fs::metadata(path).ok().map(|metadata| metadata.dev())
+}
+
+#[cfg(not(unix))]
+fn path_device(_path: &Path) -> Option<u64> {
+    None
+}
+
+#[cfg(unix)]
+fn allocated_size(metadata_ext: Option<&MetaDataExt>) -> Option<u64> {
+    // `st_blocks` is always counted in 512 byte units
+    metadata_ext.map(|metadata_ext| metadata_ext.st_blocks * 512)
+}
+
+#[cfg(not(unix))]
+fn allocated_size(_metadata_ext: Option<&MetaDataExt>) -> Option<u64> {
+    None
+}
+
+struct Output<W: Write> {
+    out: W,
+    human_readable: bool,
+    json: bool,
+    count: usize,
+}
+
+impl<W: Write> Output<W> {
+    fn begin(&mut self) -> io::Result<()> {
+        if self.json {
+            write!(self.out, "[")?;
+        }
+        Ok(())
+    }
+
+    fn dir(&mut self, path: &str, size: u64, depth: usize) -> io::Result<()> {
+        if self.json {
+            let separator = if self.count == 0 { "" } else { "," };
+            write!(
+                self.out,
+                "{}\n  {{\"path\": {}, \"size\": {}, \"depth\": {}}}",
+                separator,
+                json_string(path),
+                size,
+                depth
+            )?;
+        } else if self.human_readable {
+            writeln!(self.out, "{}\t{}", human_size(size), path)?;
+        } else {
+            // Like `du`, plain sizes are printed in 1024 byte units
+            writeln!(self.out, "{}\t{}", size.div_ceil(1024), path)?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn end(&mut self) -> io::Result<()> {
+        if self.json {
+            let newline = if self.count == 0 { "" } else { "\n" };
+            writeln!(self.out, "{}]", newline)?;
+        }
+        self.out.flush()
+    }
+}
+
+fn run(options: &Options) -> io::Result<bool> {
+    let stdout = io::stdout();
+    let mut output = Output {
+        out: BufWriter::new(stdout.lock()),
+        human_readable: options.human_readable,
+        json: options.json,
+        count: 0,
+    };
+    let mut top_dirs = Vec::new();
+    let mut success = true;
+
+    output.begin()?;
+    let excludes = options.excludes.clone();
+    let apparent_size = options.apparent_size;
+    let one_file_system = options.one_file_system;
+
+    // All PATHs are walked at once, so hard links shared between them are
+    // counted once, and PATHs inside another PATH are skipped
+    let mut walk_dir = WalkDirGeneric::<DuState>::new_multi(&options.paths)
+        .dedupe_roots(true)
+        .skip_hidden(false)
+        .read_metadata(true)
+        .read_metadata_ext(true)
+        .dedupe_inodes(!options.count_links)
+        .parallelism(options.parallelism())
+        .process_read_dir(move |depth, path, dir_device, children| {
+            if !one_file_system || depth.is_none() {
+                return;
+            }
+            if dir_device.is_none() {
+                // Reading a PATH, the state of its subdirectories keeps its
+                // device
+                *dir_device = path_device(path);
+            }
+            if let Some(dir_device) = *dir_device {
+                for dir_entry in children.iter_mut().flatten() {
+                    if device(dir_entry).is_some_and(|device| device != dir_device) {
+                        dir_entry.read_children_path = None;
+                    }
+                }
+            }
+        });
+    if !excludes.is_empty() {
+        // Excluded entries are dropped before they are checked for hard links
+        walk_dir = walk_dir.filter_name(move |file_name, _, _| {
+            let name = file_name.to_string_lossy();
+            !excludes.iter().any(|pattern| glob_match(pattern, &name))
+        });
+    }
+
+    // Files given as PATH are yielded with a folded value of 0
+    let dirs = walk_dir.fold_dirs(
+        || 0u64,
+        move |size, dir_entry| {
+            if !dir_entry.duplicate_inode {
+                *size += entry_size(dir_entry, apparent_size);
+            }
+        },
+        |size, child_size| *size += child_size,
+    );
+
+    for result in dirs {
+        match result {
+            Ok((dir_entry, size)) => {
+                if dir_entry.duplicate_inode && dir_entry.depth == 0 {
+                    // PATH already counted as part of another PATH
+                    continue;
+                }
+                // The folded value only covers the contents, the directory
+                // itself is counted on top
+                let size = if dir_entry.duplicate_inode {
+                    size
+                } else {
+                    size + entry_size(&dir_entry, apparent_size)
+                };
+                let path = dir_entry.path();
+                let path = path.to_string_lossy();
+                if options.top.is_some() {
+                    top_dirs.push((size, path.into_owned(), dir_entry.depth));
+                } else if dir_entry.depth <= options.max_depth {
+                    output.dir(&path, size, dir_entry.depth)?;
+                }
+            }
+            Err(err) => {
+                eprintln!("{}: {}", PROGRAM, err);
+                success = false;
+            }
+        }
+    }
+
+    if let Some(top) = options.top {
+        top_dirs.retain(|(_, _, depth)| *depth <= options.max_depth);
+        top_dirs.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+        for (size, path, depth) in top_dirs.into_iter().take(top) {
+            output.dir(&path, size, depth)?;
+        }
+    }
+    output.end()?;
+
+    Ok(success)
+}
+
+fn main() {
+    let options = Options::parse();
+    match run(&options) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("{}: {}", PROGRAM, err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,172 @@
+//! `jwalk duplicates`: report files with identical contents. Only built with
+//! one of the hash features blake3, sha2 or xxh3.
+
+use std::ffi::OsString;
+
+pub const USAGE: &str = "\
+Usage: jwalk duplicates [OPTION]... [PATH]...
+
+Find files with identical contents below each PATH (default: the current
+directory). Files are compared by size, then by a hash of their first and
+last KiB and finally by a hash of their complete contents. Hard links to the
+same file are reported once.
+
+Prints the paths of each set of duplicates followed by an empty line.
+
+Options:
+  --json             print the sets as JSON array of objects with the
+                     fields size, hash and paths
+  --min-size N       ignore files smaller than N bytes (default: 1)
+  --partial-kib N    KiB hashed at the start and end of files to narrow down
+                     candidates, 0 to skip this step (default: 64)
+  --algorithm NAME   blake3, sha256 or xxh3 (default: the first available)
+  -a, --all          include hidden files
+  -L, --follow       follow symbolic links
+  -j, --threads N    number of threads to use (default: all cores)
+  --help             print this help and exit
+";
+
+pub fn main(args: Vec<OsString>) -> bool {
+    use std::io::{self, BufWriter, Write};
+    use std::path::PathBuf;
+
+    use jwalk_meta::duplicates::{find_duplicates, DuplicateOptions};
+    use jwalk_meta::{HashAlgorithm, Parallelism, WalkDir};
+
+    use crate::common::{json_string, parse_number, usage_error};
+
+    const PROGRAM: &str = "jwalk duplicates";
+
+    #[cfg(feature = "blake3")]
+    let mut algorithm = HashAlgorithm::Blake3;
+    #[cfg(all(not(feature = "blake3"), feature = "xxh3"))]
+    let mut algorithm = HashAlgorithm::Xxh3;
+    #[cfg(all(not(feature = "blake3"), not(feature = "xxh3")))]
+    let mut algorithm = HashAlgorithm::Sha256;
+
+    let mut json = false;
+    let mut min_size = 1;
+    let mut partial_kib = 64;
+    let mut skip_hidden = true;
+    let mut follow_links = false;
+    let mut threads = None;
+    let mut paths = Vec::new();
+
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let arg_str = match arg.to_str() {
+            Some(arg_str) if arg_str.starts_with('-') && arg_str != "-" => arg_str.to_owned(),
+            _ => {
+                paths.push(PathBuf::from(arg));
+                continue;
+            }
+        };
+        // Accept `--option=value` as well as `--option value`
+        let (name, inline_value) = match arg_str.split_once('=') {
+            Some((name, value)) if name.starts_with("--") => {
+                (name.to_owned(), Some(value.to_owned()))
+            }
+            _ => (arg_str, None),
+        };
+        let mut value = |name: &str| -> String {
+            inline_value.clone().unwrap_or_else(|| {
+                args.next()
+                    .and_then(|value| value.into_string().ok())
+                    .unwrap_or_else(|| {
+                        usage_error(PROGRAM, &format!("option '{}' requires an argument", name))
+                    })
+            })
+        };
+        match name.as_str() {
+            "--json" => json = true,
+            "--min-size" => min_size = parse_number(PROGRAM, &name, &value(&name)),
+            "--partial-kib" => partial_kib = parse_number(PROGRAM, &name, &value(&name)),
+            "--algorithm" => {
+                algorithm = match value(&name).as_str() {
+                    #[cfg(feature = "blake3")]
+                    "blake3" => HashAlgorithm::Blake3,
+                    #[cfg(feature = "sha2")]
+                    "sha256" => HashAlgorithm::Sha256,
+                    #[cfg(feature = "xxh3")]
+                    "xxh3" => HashAlgorithm::Xxh3,
+                    other => usage_error(PROGRAM, &format!("unsupported algorithm '{}'", other)),
+                }
+            }
+            "-a" | "--all" => skip_hidden = false,
+            "-L" | "--follow" => follow_links = true,
+            "-j" | "--threads" => threads = Some(parse_number(PROGRAM, &name, &value(&name))),
+            "--" => {
+                paths.extend(args.by_ref().map(PathBuf::from));
+            }
+            _ => usage_error(PROGRAM, &format!("unrecognized option '{}'", name)),
+        }
+    }
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+
+    let parallelism = match threads {
+        Some(1) => Parallelism::Serial,
+        Some(threads) => Parallelism::RayonNewPool(threads),
+        None => Parallelism::RayonNewPool(0),
+    };
+    let walk_dir = WalkDir::new_multi(paths)
+        .skip_hidden(skip_hidden)
+        .follow_links(follow_links)
+        .parallelism(parallelism);
+    let options = DuplicateOptions {
+        min_size,
+        partial_kib,
+        ..DuplicateOptions::new(algorithm)
+    };
+    let duplicates = find_duplicates(walk_dir, &options);
+    for err in &duplicates.errors {
+        eprintln!("{}: {}", PROGRAM, err);
+    }
+
+    let write = || -> io::Result<()> {
+        let stdout = io::stdout();
+        let mut out = BufWriter::new(stdout.lock());
+        if json {
+            write!(out, "[")?;
+        }
+        for (index, set) in duplicates.sets.iter().enumerate() {
+            if json {
+                let separator = if index == 0 { "" } else { "," };
+                let hash: String = set.hash.iter().map(|b| format!("{:02x}", b)).collect();
+                let paths: Vec<String> = set
+                    .paths
+                    .iter()
+                    .map(|path| json_string(&path.to_string_lossy()))
+                    .collect();
+                write!(
+                    out,
+                    "{}\n  {{\"size\": {}, \"hash\": \"{}\", \"paths\": [{}]}}",
+                    separator,
+                    set.size,
+                    hash,
+                    paths.join(", ")
+                )?;
+            } else {
+                for path in &set.paths {
+                    writeln!(out, "{}", path.display())?;
+                }
+                writeln!(out)?;
+            }
+        }
+        if json {
+            let newline = if duplicates.sets.is_empty() { "" } else { "\n" };
+            writeln!(out, "{}]", newline)?;
+        }
+        out.flush()
+    };
+    match write() {
+        Ok(()) => {}
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("{}: {}", PROGRAM, err);
+            return false;
+        }
+    }
+    duplicates.errors.is_empty()
+}
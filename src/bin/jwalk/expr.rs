@@ -0,0 +1,514 @@
+//! Parsing and evaluation of find style expressions.
+//!
+//! Expressions are evaluated from `process_read_dir`, i.e. on the worker
+//! thread that read the directory. All tests work on the prefetched
+//! [`MetaData`](jwalk_meta::MetaData) and
+//! [`MetaDataExt`](jwalk_meta::MetaDataExt) of the entry, only `-empty` on a
+//! directory needs to touch the file system.
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use jwalk_meta::DirEntry;
+
+use crate::common::glob_match;
+use crate::FindState;
+
+/// Comparison of a numeric argument given as `+N`, `-N` or `N`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Comparison {
+    Less(u64),
+    Equal(u64),
+    Greater(u64),
+}
+
+impl Comparison {
+    fn parse(arg: &str) -> Option<Comparison> {
+        if let Some(n) = arg.strip_prefix('+') {
+            n.parse().ok().map(Comparison::Greater)
+        } else if let Some(n) = arg.strip_prefix('-') {
+            n.parse().ok().map(Comparison::Less)
+        } else {
+            arg.parse().ok().map(Comparison::Equal)
+        }
+    }
+
+    fn matches(self, value: u64) -> bool {
+        match self {
+            Comparison::Less(n) => value < n,
+            Comparison::Equal(n) => value == n,
+            Comparison::Greater(n) => value > n,
+        }
+    }
+}
+
+/// How the mode given to `-perm` is compared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PermMatch {
+    /// `-perm MODE`: the permission bits are exactly `MODE`.
+    Exact,
+    /// `-perm -MODE`: all bits of `MODE` are set.
+    All,
+    /// `-perm /MODE`: any bit of `MODE` is set.
+    Any,
+}
+
+/// Action to perform with a matching entry.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Action {
+    Print,
+    Print0,
+    /// Run a command. With `batch` the paths are appended to `command` and
+    /// the command is run for many paths at once (`-exec ... {} +`), otherwise
+    /// every `{}` is replaced by the path (`-exec ... ;`).
+    Exec {
+        command: Vec<String>,
+        batch: bool,
+    },
+}
+
+/// Node of a find expression.
+#[derive(Debug)]
+pub enum Expr {
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Not(Box<Expr>),
+    True,
+    Name(String),
+    Path(String),
+    Type(Vec<char>),
+    /// Size rounded up to multiples of the unit.
+    Size(Comparison, u64),
+    /// Age of the last modification in whole days.
+    Mtime(Comparison),
+    Newer(SystemTime),
+    User(u32),
+    Perm(PermMatch, u32),
+    Empty,
+    Prune,
+    /// Index into the list of actions.
+    Action(usize),
+}
+
+/// Options that may appear anywhere in the expression.
+#[derive(Debug, Default)]
+pub struct GlobalOptions {
+    pub min_depth: Option<usize>,
+    pub max_depth: Option<usize>,
+    pub sort: bool,
+    pub follow_links: bool,
+    pub threads: Option<usize>,
+}
+
+/// Parsed command line expression.
+#[derive(Debug)]
+pub struct Expression {
+    pub expr: Expr,
+    pub actions: Vec<Action>,
+    pub options: GlobalOptions,
+    now: SystemTime,
+}
+
+impl Expression {
+    /// Parse the expression part of the command line. Without any action,
+    /// `-print` is appended.
+    pub fn parse(args: &[String]) -> Result<Expression, String> {
+        let mut parser = Parser {
+            args,
+            pos: 0,
+            actions: Vec::new(),
+            options: GlobalOptions::default(),
+        };
+        let mut expr = if args.is_empty() {
+            Expr::True
+        } else {
+            parser.parse_or()?
+        };
+        if let Some(arg) = args.get(parser.pos) {
+            return Err(format!("unexpected '{}'", arg));
+        }
+        if parser.actions.is_empty() {
+            parser.actions.push(Action::Print);
+            expr = Expr::And(Box::new(expr), Box::new(Expr::Action(0)));
+        }
+        if parser.actions.len() > 64 {
+            return Err("too many actions".to_owned());
+        }
+        Ok(Expression {
+            expr,
+            actions: parser.actions,
+            options: parser.options,
+            now: SystemTime::now(),
+        })
+    }
+
+    /// Returns `true` if any test needs `MetaData`. Tests needing
+    /// `MetaDataExt` need it as well, the extended metadata is only read
+    /// together with the basic one.
+    pub fn needs_metadata(&self) -> bool {
+        self.needs_metadata_ext()
+            || self.expr.any(&|expr| {
+                matches!(
+                    expr,
+                    Expr::Size(..) | Expr::Mtime(_) | Expr::Newer(_) | Expr::Empty
+                )
+            })
+    }
+
+    /// Returns `true` if any test needs `MetaDataExt`.
+    pub fn needs_metadata_ext(&self) -> bool {
+        self.expr
+            .any(&|expr| matches!(expr, Expr::User(_) | Expr::Perm(..)))
+    }
+
+    /// Evaluate the expression for `dir_entry`. Returns the set of actions to
+    /// perform as bit mask and whether the directory should be pruned.
+    pub fn eval(&self, dir_entry: &DirEntry<FindState>) -> (u64, bool) {
+        let mut state = EvalState {
+            actions: 0,
+            prune: false,
+        };
+        self.expr.eval(self, dir_entry, &mut state);
+        (state.actions, state.prune)
+    }
+}
+
+struct EvalState {
+    actions: u64,
+    prune: bool,
+}
+
+impl Expr {
+    fn any(&self, f: &dyn Fn(&Expr) -> bool) -> bool {
+        match self {
+            Expr::And(a, b) | Expr::Or(a, b) => a.any(f) || b.any(f),
+            Expr::Not(a) => a.any(f),
+            expr => f(expr),
+        }
+    }
+
+    fn eval(
+        &self,
+        expression: &Expression,
+        dir_entry: &DirEntry<FindState>,
+        state: &mut EvalState,
+    ) -> bool {
+        let metadata = dir_entry.metadata.as_ref();
+        match self {
+            Expr::And(a, b) => {
+                a.eval(expression, dir_entry, state) && b.eval(expression, dir_entry, state)
+            }
+            Expr::Or(a, b) => {
+                a.eval(expression, dir_entry, state) || b.eval(expression, dir_entry, state)
+            }
+            Expr::Not(a) => !a.eval(expression, dir_entry, state),
+            Expr::True => true,
+            Expr::Name(pattern) => glob_match(pattern, &dir_entry.file_name.to_string_lossy()),
+            Expr::Path(pattern) => glob_match(pattern, &dir_entry.path().to_string_lossy()),
+            Expr::Type(types) => {
+                let file_type = dir_entry.file_type;
+                types.iter().any(|t| match t {
+                    'f' => file_type.is_file(),
+                    'd' => file_type.is_dir(),
+                    'l' => file_type.is_symlink(),
+                    _ => false,
+                })
+            }
+            Expr::Size(comparison, unit) => {
+                metadata.is_some_and(|metadata| comparison.matches(metadata.size.div_ceil(*unit)))
+            }
+            Expr::Mtime(comparison) => {
+                metadata
+                    .and_then(|metadata| metadata.modified)
+                    .is_some_and(|modified| {
+                        let age = expression
+                            .now
+                            .duration_since(modified)
+                            .map_or(0, |age| age.as_secs());
+                        comparison.matches(age / 86400)
+                    })
+            }
+            Expr::Newer(reference) => metadata
+                .and_then(|metadata| metadata.modified)
+                .is_some_and(|modified| modified > *reference),
+            Expr::User(uid) => user_id(dir_entry) == Some(*uid),
+            Expr::Perm(perm_match, mode) => {
+                permission_bits(dir_entry).is_some_and(|bits| match perm_match {
+                    PermMatch::Exact => bits == *mode,
+                    PermMatch::All => bits & mode == *mode,
+                    PermMatch::Any => *mode == 0 || bits & mode != 0,
+                })
+            }
+            Expr::Empty => {
+                if dir_entry.file_type.is_dir() {
+                    fs::read_dir(dir_entry.path())
+                        .map(|mut read_dir| read_dir.next().is_none())
+                        .unwrap_or(false)
+                } else {
+                    dir_entry.file_type.is_file() && metadata.is_some_and(|m| m.size == 0)
+                }
+            }
+            Expr::Prune => {
+                state.prune = true;
+                true
+            }
+            Expr::Action(index) => {
+                state.actions |= 1 << index;
+                true
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn user_id(dir_entry: &DirEntry<FindState>) -> Option<u32> {
+    dir_entry.metadata_ext.as_ref().map(|ext| ext.st_uid)
+}
+
+#[cfg(not(unix))]
+fn user_id(_dir_entry: &DirEntry<FindState>) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn permission_bits(dir_entry: &DirEntry<FindState>) -> Option<u32> {
+    dir_entry
+        .metadata_ext
+        .as_ref()
+        .map(|ext| ext.st_mode & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn permission_bits(_dir_entry: &DirEntry<FindState>) -> Option<u32> {
+    None
+}
+
+#[cfg(unix)]
+fn lookup_user(name: &str) -> Option<u32> {
+    if let Ok(uid) = name.parse() {
+        return Some(uid);
+    }
+    let name = std::ffi::CString::new(name).ok()?;
+    // getpwnam returns a pointer into static storage, only the uid is copied
+    let passwd = unsafe { libc::getpwnam(name.as_ptr()) };
+    if passwd.is_null() {
+        None
+    } else {
+        Some(unsafe { (*passwd).pw_uid })
+    }
+}
+
+#[cfg(not(unix))]
+fn lookup_user(name: &str) -> Option<u32> {
+    name.parse().ok()
+}
+
+// Recursive descent parser for
+//
+//   or      := and ( ( "-o" | "-or" ) and )*
+//   and     := unary ( [ "-a" | "-and" ] unary )*
+//   unary   := ( "!" | "-not" ) unary | "(" or ")" | primary
+struct Parser<'a> {
+    args: &'a [String],
+    pos: usize,
+    actions: Vec<Action>,
+    options: GlobalOptions,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&str> {
+        self.args.get(self.pos).map(String::as_str)
+    }
+
+    fn next_arg(&mut self, primary: &str) -> Result<&str, String> {
+        let arg = self
+            .args
+            .get(self.pos)
+            .ok_or_else(|| format!("missing argument to '{}'", primary))?;
+        self.pos += 1;
+        Ok(arg)
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_and()?;
+        while matches!(self.peek(), Some("-o") | Some("-or")) {
+            self.pos += 1;
+            expr = Expr::Or(Box::new(expr), Box::new(self.parse_and()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.parse_unary()?;
+        loop {
+            match self.peek() {
+                Some("-a") | Some("-and") => self.pos += 1,
+                Some("-o") | Some("-or") | Some(")") | None => return Ok(expr),
+                Some(_) => {}
+            }
+            expr = Expr::And(Box::new(expr), Box::new(self.parse_unary()?));
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, String> {
+        match self.peek() {
+            Some("!") | Some("-not") => {
+                self.pos += 1;
+                Ok(Expr::Not(Box::new(self.parse_unary()?)))
+            }
+            Some("(") => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                if self.peek() != Some(")") {
+                    return Err("missing ')'".to_owned());
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(_) => self.parse_primary(),
+            None => Err("expected an expression".to_owned()),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, String> {
+        let primary = self.next_arg("")?.to_owned();
+        let primary = primary.as_str();
+        let expr = match primary {
+            "-name" => Expr::Name(self.next_arg(primary)?.to_owned()),
+            "-path" => Expr::Path(self.next_arg(primary)?.to_owned()),
+            "-type" => {
+                let arg = self.next_arg(primary)?;
+                let types: Vec<char> = arg.split(',').flat_map(str::chars).collect();
+                if types.is_empty() || types.iter().any(|t| !matches!(t, 'f' | 'd' | 'l')) {
+                    return Err(format!("unknown argument to -type: {}", arg));
+                }
+                Expr::Type(types)
+            }
+            "-size" => {
+                let arg = self.next_arg(primary)?;
+                let (number, unit) = match arg.char_indices().last() {
+                    Some((i, c)) if c.is_ascii_alphabetic() => {
+                        let unit = match c {
+                            'c' => 1,
+                            'w' => 2,
+                            'b' => 512,
+                            'k' => 1024,
+                            'M' => 1024 * 1024,
+                            'G' => 1024 * 1024 * 1024,
+                            _ => return Err(format!("invalid -size argument: {}", arg)),
+                        };
+                        (&arg[..i], unit)
+                    }
+                    _ => (arg, 512),
+                };
+                let comparison = Comparison::parse(number)
+                    .ok_or_else(|| format!("invalid -size argument: {}", arg))?;
+                Expr::Size(comparison, unit)
+            }
+            "-mtime" => {
+                let arg = self.next_arg(primary)?;
+                Expr::Mtime(
+                    Comparison::parse(arg)
+                        .ok_or_else(|| format!("invalid -mtime argument: {}", arg))?,
+                )
+            }
+            "-newer" => {
+                let arg = self.next_arg(primary)?;
+                let modified = fs::metadata(arg)
+                    .and_then(|metadata| metadata.modified())
+                    .map_err(|err| format!("'{}': {}", arg, err))?;
+                Expr::Newer(modified)
+            }
+            "-user" => {
+                let arg = self.next_arg(primary)?;
+                Expr::User(lookup_user(arg).ok_or_else(|| format!("unknown user: {}", arg))?)
+            }
+            "-perm" => {
+                let arg = self.next_arg(primary)?;
+                let (perm_match, mode) = if let Some(mode) = arg.strip_prefix('-') {
+                    (PermMatch::All, mode)
+                } else if let Some(mode) = arg.strip_prefix('/') {
+                    (PermMatch::Any, mode)
+                } else {
+                    (PermMatch::Exact, arg)
+                };
+                let mode = u32::from_str_radix(mode, 8)
+                    .ok()
+                    .filter(|mode| *mode <= 0o7777)
+                    .ok_or_else(|| format!("invalid mode: {}", arg))?;
+                Expr::Perm(perm_match, mode)
+            }
+            "-empty" => Expr::Empty,
+            "-prune" => Expr::Prune,
+            "-true" => Expr::True,
+            "-false" => Expr::Not(Box::new(Expr::True)),
+            "-print" => self.action(Action::Print),
+            "-print0" => self.action(Action::Print0),
+            "-exec" => {
+                let mut command = Vec::new();
+                let batch = loop {
+                    let arg = self
+                        .args
+                        .get(self.pos)
+                        .ok_or_else(|| "missing argument to '-exec'".to_owned())?;
+                    self.pos += 1;
+                    match arg.as_str() {
+                        ";" => break false,
+                        "+" if command.last().is_some_and(|last| last == "{}") => {
+                            command.pop();
+                            break true;
+                        }
+                        _ => command.push(arg.clone()),
+                    }
+                };
+                if command.is_empty() {
+                    return Err("missing command to '-exec'".to_owned());
+                }
+                self.action(Action::Exec { command, batch })
+            }
+            "-mindepth" | "-maxdepth" | "-threads" => {
+                let arg = self.next_arg(primary)?;
+                let value = arg
+                    .parse()
+                    .map_err(|_| format!("invalid argument to {}: {}", primary, arg))?;
+                match primary {
+                    "-mindepth" => self.options.min_depth = Some(value),
+                    "-maxdepth" => self.options.max_depth = Some(value),
+                    _ => self.options.threads = Some(value),
+                }
+                Expr::True
+            }
+            "-sort" => {
+                self.options.sort = true;
+                Expr::True
+            }
+            "-L" | "-follow" => {
+                self.options.follow_links = true;
+                Expr::True
+            }
+            _ => return Err(format!("unknown predicate '{}'", primary)),
+        };
+        Ok(expr)
+    }
+
+    fn action(&mut self, action: Action) -> Expr {
+        self.actions.push(action);
+        Expr::Action(self.actions.len() - 1)
+    }
+}
+
+/// Paths from the command line, the expression starts at the first argument
+/// beginning with `-`, `!` or `(`.
+pub fn split_paths(args: Vec<std::ffi::OsString>) -> (Vec<PathBuf>, Vec<std::ffi::OsString>) {
+    let start = args
+        .iter()
+        .position(|arg| {
+            arg.to_str()
+                .is_some_and(|arg| (arg.starts_with('-') && arg != "-") || arg == "!" || arg == "(")
+        })
+        .unwrap_or(args.len());
+    let mut paths = args;
+    let expression = paths.split_off(start);
+    (paths.into_iter().map(PathBuf::from).collect(), expression)
+}
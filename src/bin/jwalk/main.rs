@@ -0,0 +1,336 @@
+//! Search for files in directory trees, similar to `find`.
+//!
+//! ```text
+//! jwalk [PATH]... [EXPRESSION]
+//! jwalk duplicates [OPTION]... [PATH]...
+//! ```
+//!
+//! Run `jwalk --help` for the supported expression. The `duplicates`
+//! subcommand is only built with one of the hash features blake3, sha2 or xxh3.
+
+use std::collections::VecDeque;
+use std::env;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::process::{self, Child, Command};
+use std::thread;
+
+use jwalk_meta::{Parallelism, WalkDirGeneric};
+
+#[path = "../common/mod.rs"]
+mod common;
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+mod duplicates;
+mod expr;
+
+use common::usage_error;
+use expr::{Action, Expression};
+
+const PROGRAM: &str = "jwalk";
+
+const SYNOPSIS: &str = "Usage: jwalk [PATH]... [EXPRESSION]\n";
+
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+const DUPLICATES_SYNOPSIS: &str = "       jwalk duplicates [OPTION]... [PATH]...\n";
+
+const USAGE: &str = "
+Walk each PATH (default: the current directory) in parallel and perform the
+actions of EXPRESSION for every matching entry. Without action, matching
+entries are printed.
+
+Operators (in order of decreasing precedence):
+  ( EXPR )  ! EXPR  -not EXPR  EXPR EXPR  EXPR -a EXPR  EXPR -o EXPR
+
+Tests:
+  -name GLOB       file name matches GLOB
+  -path GLOB       path matches GLOB
+  -type [fdl]      file, directory or symbolic link, e.g. -type f,l
+  -size [+-]N[cwbkMG]
+                   size rounded up to units (default: 512 byte blocks)
+  -mtime [+-]N     modified N days ago
+  -newer FILE      modified more recently than FILE
+  -user NAME       owned by user NAME or numeric user id
+  -perm [-/]MODE   permission bits are exactly, all of (-) or any of (/)
+                   the octal MODE
+  -empty           empty file or directory
+  -true, -false
+
+Actions:
+  -prune           don't descend into the directory
+  -print           print the path followed by a newline
+  -print0          print the path followed by a NUL character
+  -exec CMD ;      run CMD for every entry, {} is replaced by the path
+  -exec CMD {} +   run CMD for many entries at once, batches run in parallel
+
+Options:
+  -mindepth N      don't apply tests or actions at levels less than N
+  -maxdepth N      descend at most N levels below the paths
+  -sort            print entries sorted by name
+  -L, -follow      follow symbolic links
+  -threads N       number of threads to use (default: all cores)
+  --help           print this help and exit
+";
+
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+const DUPLICATES_HINT: &str = "
+Run 'jwalk duplicates --help' for finding files with identical contents.
+";
+
+// Maximum number of paths and total length of paths passed to one batched
+// command, well below the usual argument size limits.
+const BATCH_MAX_PATHS: usize = 4096;
+const BATCH_MAX_BYTES: usize = 128 * 1024;
+
+/// Client state: the bit mask of actions to perform for an entry.
+pub type FindState = ((), u64);
+
+/// Pending paths of an `-exec ... {} +` action.
+struct Batch {
+    command: Vec<String>,
+    paths: Vec<PathBuf>,
+    bytes: usize,
+}
+
+/// Commands started by `-exec`, at most `max_running` at the same time.
+struct Jobs {
+    running: VecDeque<Child>,
+    max_running: usize,
+    success: bool,
+}
+
+impl Jobs {
+    fn spawn(&mut self, program: &str, args: &[OsString]) {
+        while self.running.len() >= self.max_running {
+            self.wait_oldest();
+        }
+        match Command::new(program).args(args).spawn() {
+            Ok(child) => self.running.push_back(child),
+            Err(err) => {
+                eprintln!("{}: '{}': {}", PROGRAM, program, err);
+                self.success = false;
+            }
+        }
+    }
+
+    fn wait_oldest(&mut self) {
+        if let Some(mut child) = self.running.pop_front() {
+            if !child.wait().is_ok_and(|status| status.success()) {
+                self.success = false;
+            }
+        }
+    }
+
+    fn wait_all(&mut self) {
+        while !self.running.is_empty() {
+            self.wait_oldest();
+        }
+    }
+}
+
+fn write_path<W: Write>(out: &mut W, path: &Path, terminator: u8) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        out.write_all(path.as_os_str().as_bytes())?;
+    }
+    #[cfg(not(unix))]
+    {
+        out.write_all(path.to_string_lossy().as_bytes())?;
+    }
+    out.write_all(&[terminator])
+}
+
+fn run(paths: Vec<PathBuf>, expression: Expression) -> io::Result<bool> {
+    let options = &expression.options;
+    let min_depth = options.min_depth.unwrap_or(0);
+    let threads = options
+        .threads
+        .unwrap_or_else(|| thread::available_parallelism().map_or(1, |threads| threads.get()));
+    let parallelism = if threads == 1 {
+        Parallelism::Serial
+    } else {
+        Parallelism::RayonNewPool(threads)
+    };
+
+    let mut walk_dir = WalkDirGeneric::<FindState>::new_multi(paths)
+        .skip_hidden(false)
+        .sort(options.sort)
+        .follow_links(options.follow_links)
+        .min_depth(min_depth)
+        .read_metadata(expression.needs_metadata())
+        .read_metadata_ext(expression.needs_metadata_ext())
+        .parallelism(parallelism);
+    if let Some(max_depth) = options.max_depth {
+        walk_dir = walk_dir.max_depth(max_depth);
+    }
+
+    let actions = expression.actions.clone();
+    let expression = std::sync::Arc::new(expression);
+    let walk_dir = walk_dir.process_read_dir(move |_, _, _, children| {
+        for dir_entry in children.iter_mut().flatten() {
+            if dir_entry.depth < min_depth {
+                continue;
+            }
+            let (actions, prune) = expression.eval(dir_entry);
+            if prune {
+                dir_entry.read_children_path = None;
+            }
+            dir_entry.client_state = actions;
+        }
+    });
+
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    let mut jobs = Jobs {
+        running: VecDeque::new(),
+        max_running: threads,
+        success: true,
+    };
+    let mut batches: Vec<Option<Batch>> = actions
+        .iter()
+        .map(|action| match action {
+            Action::Exec {
+                command,
+                batch: true,
+            } => Some(Batch {
+                command: command.clone(),
+                paths: Vec::new(),
+                bytes: 0,
+            }),
+            _ => None,
+        })
+        .collect();
+    let mut success = true;
+
+    for result in walk_dir {
+        let dir_entry = match result {
+            Ok(dir_entry) => dir_entry,
+            Err(err) => {
+                eprintln!("{}: {}", PROGRAM, err);
+                success = false;
+                continue;
+            }
+        };
+        if dir_entry.client_state == 0 {
+            continue;
+        }
+        let path = dir_entry.path();
+        for (index, action) in actions.iter().enumerate() {
+            if dir_entry.client_state & (1 << index) == 0 {
+                continue;
+            }
+            match action {
+                Action::Print => write_path(&mut out, &path, b'\n')?,
+                Action::Print0 => write_path(&mut out, &path, b'\0')?,
+                Action::Exec {
+                    command,
+                    batch: false,
+                } => {
+                    let args: Vec<OsString> = command[1..]
+                        .iter()
+                        .map(|arg| replace_braces(arg, &path))
+                        .collect();
+                    out.flush()?;
+                    jobs.spawn(&command[0], &args);
+                    // Commands run for single entries keep the walk order
+                    jobs.wait_all();
+                }
+                Action::Exec { batch: true, .. } => {
+                    let batch = batches[index].as_mut().unwrap();
+                    batch.bytes += path.as_os_str().len() + 1;
+                    batch.paths.push(path.clone());
+                    if batch.paths.len() >= BATCH_MAX_PATHS || batch.bytes >= BATCH_MAX_BYTES {
+                        out.flush()?;
+                        run_batch(batch, &mut jobs);
+                    }
+                }
+            }
+        }
+    }
+
+    out.flush()?;
+    for batch in batches.iter_mut().flatten() {
+        if !batch.paths.is_empty() {
+            run_batch(batch, &mut jobs);
+        }
+    }
+    jobs.wait_all();
+
+    Ok(success && jobs.success)
+}
+
+fn replace_braces(arg: &str, path: &Path) -> OsString {
+    if !arg.contains("{}") {
+        return OsString::from(arg);
+    }
+    let mut replaced = OsString::new();
+    let mut parts = arg.split("{}");
+    if let Some(first) = parts.next() {
+        replaced.push(first);
+    }
+    for part in parts {
+        replaced.push(path.as_os_str());
+        replaced.push(part);
+    }
+    replaced
+}
+
+fn print_usage() {
+    print!("{}", SYNOPSIS);
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    print!("{}", DUPLICATES_SYNOPSIS);
+    print!("{}", USAGE);
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    print!("{}", DUPLICATES_HINT);
+}
+
+fn run_batch(batch: &mut Batch, jobs: &mut Jobs) {
+    let mut args: Vec<OsString> = batch.command[1..].iter().map(OsString::from).collect();
+    args.extend(batch.paths.drain(..).map(PathBuf::into_os_string));
+    batch.bytes = 0;
+    jobs.spawn(&batch.command[0], &args);
+}
+
+fn main() {
+    let args: Vec<OsString> = env::args_os().skip(1).collect();
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    if args.first().is_some_and(|arg| arg == "duplicates") {
+        let args: Vec<OsString> = args.into_iter().skip(1).collect();
+        if args.iter().any(|arg| arg == "--help") {
+            print!("{}", duplicates::USAGE);
+            return;
+        }
+        if !duplicates::main(args) {
+            process::exit(1);
+        }
+        return;
+    }
+    if args.iter().any(|arg| arg == "--help") {
+        print_usage();
+        return;
+    }
+    let (mut paths, expression_args) = expr::split_paths(args);
+    if paths.is_empty() {
+        paths.push(PathBuf::from("."));
+    }
+    let expression_args: Vec<String> = expression_args
+        .into_iter()
+        .map(|arg| {
+            arg.into_string()
+                .unwrap_or_else(|arg| usage_error(PROGRAM, &format!("invalid argument {:?}", arg)))
+        })
+        .collect();
+    let expression =
+        Expression::parse(&expression_args).unwrap_or_else(|err| usage_error(PROGRAM, &err));
+
+    match run(paths, expression) {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) if err.kind() == io::ErrorKind::BrokenPipe => {}
+        Err(err) => {
+            eprintln!("{}: {}", PROGRAM, err);
+            process::exit(1);
+        }
+    }
+}
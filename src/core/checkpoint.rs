@@ -0,0 +1,58 @@
+use std::cmp::Ordering;
+use std::ffi::OsString;
+
+use super::*;
+use crate::Result;
+
+/// Position in a sorted walk, used to resume an interrupted walk.
+///
+/// Created by [`DirEntryIter::checkpoint`](struct.DirEntryIter.html#method.checkpoint)
+/// and passed to
+/// [`WalkDirGeneric::resume_from`](struct.WalkDirGeneric.html#method.resume_from).
+///
+/// In sorted depth first order the position of the last entry determines the
+/// rest of the walk: every entry before it is finished, and the pending
+/// frontier consists of the entries following each of its ancestors. The
+/// checkpoint therefore stores the file names along the path of the last
+/// entry. Names are stored instead of indices, so entries added or removed in
+/// between don't shift the position.
+///
+/// With the `serde` feature the checkpoint implements `Serialize` and
+/// `Deserialize`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Checkpoint {
+    /// Index of the root of the last entry among the roots of the walk.
+    pub root: usize,
+    /// File names of the entries below the root, from the root's child down
+    /// to the last entry.
+    pub names: Vec<OsString>,
+}
+
+// Mark the entries of a directory on the path of a resume cursor. Entries
+// sorting before the cursor component are finished and neither yielded nor
+// descended into. The entry matching it was yielded before, its contents are
+// resumed with the rest of the cursor. Entries after it are walked normally.
+pub(crate) fn apply_resume_cursor<C: ClientState>(
+    dir_entry_results: &mut [Result<DirEntry<C>>],
+    cursor: &[OsString],
+) {
+    let Some((name, rest)) = cursor.split_first() else {
+        return;
+    };
+    for dir_entry in dir_entry_results.iter_mut().flatten() {
+        match dir_entry.file_name.as_os_str().cmp(name) {
+            Ordering::Less => {
+                dir_entry.skip_yield = true;
+                dir_entry.read_children_path = None;
+            }
+            Ordering::Equal => {
+                dir_entry.skip_yield = true;
+                if !rest.is_empty() {
+                    dir_entry.resume_cursor = Some(Arc::from(rest));
+                }
+            }
+            Ordering::Greater => {}
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::fs::File;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use super::*;
+
+/// Digest algorithm used by
+/// [`WalkDirGeneric::content_hash`](struct.WalkDirGeneric.html#method.content_hash).
+///
+/// Every algorithm is behind a feature of the same name: `blake3`, `sha2`
+/// and `xxh3`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    /// 32 byte BLAKE3 digest.
+    #[cfg(feature = "blake3")]
+    Blake3,
+    /// 32 byte SHA-256 digest.
+    #[cfg(feature = "sha2")]
+    Sha256,
+    /// 16 byte XXH3-128 digest, not cryptographic.
+    #[cfg(feature = "xxh3")]
+    Xxh3,
+}
+
+/// Configuration of content hashing, see
+/// [`WalkDirGeneric::content_hash`](struct.WalkDirGeneric.html#method.content_hash).
+#[derive(Clone, Debug)]
+pub struct ContentHash {
+    /// Digest algorithm.
+    pub algorithm: HashAlgorithm,
+    /// Files larger than this number of bytes are not hashed.
+    pub max_size: Option<u64>,
+    /// Only hash the first and the last `partial_kib` KiB of files, together
+    /// with their size. Files up to twice that size are hashed completely.
+    pub partial_kib: Option<u64>,
+}
+
+impl ContentHash {
+    /// Hash complete files of any size with `algorithm`.
+    pub fn new(algorithm: HashAlgorithm) -> ContentHash {
+        ContentHash {
+            algorithm,
+            max_size: None,
+            partial_kib: None,
+        }
+    }
+
+    // Hash the contents of a regular file, storing the digest or the error on
+    // the entry. Other entries and files above `max_size` are left alone.
+    pub(crate) fn hash_entry<C: ClientState>(&self, dir_entry: &mut DirEntry<C>) {
+        if !dir_entry.file_type.is_file() {
+            return;
+        }
+        let path = dir_entry.full_path();
+        match self.hash_file(&path, dir_entry.metadata.as_ref().map(|m| m.size)) {
+            Ok(content_hash) => dir_entry.content_hash = content_hash,
+            Err(err) => {
+                dir_entry.content_hash_error = Some(Error::from_path(dir_entry.depth, path, err))
+            }
+        }
+    }
+
+    pub(crate) fn hash_file(&self, path: &Path, size: Option<u64>) -> io::Result<Option<Vec<u8>>> {
+        if let (Some(max_size), Some(size)) = (self.max_size, size) {
+            if size > max_size {
+                return Ok(None);
+            }
+        }
+        let mut file = File::open(path)?;
+        let size = match size {
+            Some(size) => size,
+            None => file.metadata()?.len(),
+        };
+        if self.max_size.is_some_and(|max_size| size > max_size) {
+            return Ok(None);
+        }
+
+        let mut hasher = Hasher::new(self.algorithm);
+        match self.partial_kib.map(|kib| kib.saturating_mul(1024)) {
+            Some(part) if size > part.saturating_mul(2) => {
+                hasher.update(&size.to_le_bytes());
+                copy_to_hasher(&mut (&mut file).take(part), &mut hasher)?;
+                file.seek(SeekFrom::End(-(part as i64)))?;
+                copy_to_hasher(&mut file.take(part), &mut hasher)?;
+            }
+            _ => copy_to_hasher(&mut file, &mut hasher)?,
+        }
+        Ok(Some(hasher.finalize()))
+    }
+}
+
+fn copy_to_hasher<R: Read>(reader: &mut R, hasher: &mut Hasher) -> io::Result<()> {
+    let mut buffer = vec![0; 64 * 1024];
+    loop {
+        match reader.read(&mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(len) => hasher.update(&buffer[..len]),
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+enum Hasher {
+    #[cfg(feature = "blake3")]
+    Blake3(Box<blake3::Hasher>),
+    #[cfg(feature = "sha2")]
+    Sha256(sha2::Sha256),
+    #[cfg(feature = "xxh3")]
+    Xxh3(Box<xxhash_rust::xxh3::Xxh3>),
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Hasher {
+        match algorithm {
+            #[cfg(feature = "blake3")]
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+            #[cfg(feature = "sha2")]
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Digest::new()),
+            #[cfg(feature = "xxh3")]
+            HashAlgorithm::Xxh3 => Hasher::Xxh3(Box::default()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            #[cfg(feature = "blake3")]
+            Hasher::Blake3(hasher) => {
+                hasher.update(data);
+            }
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(hasher) => sha2::Digest::update(hasher, data),
+            #[cfg(feature = "xxh3")]
+            Hasher::Xxh3(hasher) => hasher.update(data),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            #[cfg(feature = "blake3")]
+            Hasher::Blake3(hasher) => hasher.finalize().as_bytes().to_vec(),
+            #[cfg(feature = "sha2")]
+            Hasher::Sha256(hasher) => sha2::Digest::finalize(hasher).to_vec(),
+            #[cfg(feature = "xxh3")]
+            Hasher::Xxh3(hasher) => hasher.digest128().to_be_bytes().to_vec(),
+        }
+    }
+}
@@ -1,10 +1,46 @@
+use std::any::Any;
+use std::cell::RefCell;
 use std::ffi::{OsStr, OsString};
 use std::fmt;
 use std::fs::{self, FileType};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, OnceLock};
 
-use crate::{ClientState, Error, ReadDirSpec, Result, MetaData, get_metadata_ext, MetaDataExt};
+use crate::{
+    get_metadata_ext, ClientState, Error, FileName, MetaData, MetaDataExt, ReadDirSpec, Result,
+    RetryPolicy,
+};
+
+/// Directory passed while following symbolic links, remembered to detect
+/// loops.
+#[derive(Debug)]
+pub(crate) struct FollowLinkAncestor {
+    pub(crate) path: Arc<Path>,
+    // Identity of the directory, `None` if it couldn't be determined. Read
+    // when a link is followed below the directory, unless already known.
+    inode_key: OnceLock<Option<(u64, u64)>>,
+}
+
+impl FollowLinkAncestor {
+    pub(crate) fn new(path: Arc<Path>, inode_key: Option<(u64, u64)>) -> FollowLinkAncestor {
+        let ancestor = FollowLinkAncestor {
+            path,
+            inode_key: OnceLock::new(),
+        };
+        if inode_key.is_some() {
+            let _ = ancestor.inode_key.set(inode_key);
+        }
+        ancestor
+    }
+
+    pub(crate) fn inode_key(&self) -> Option<(u64, u64)> {
+        *self.inode_key.get_or_init(|| {
+            fs::metadata(&self.path)
+                .ok()
+                .and_then(|metadata| get_metadata_ext(&metadata).inode_key())
+        })
+    }
+}
 
 /// Representation of a file or directory.
 ///
@@ -17,7 +53,7 @@ pub struct DirEntry<C: ClientState> {
     /// started.
     pub depth: usize,
     /// File name of this entry without leading path component.
-    pub file_name: OsString,
+    pub file_name: FileName,
     /// File type for the file/directory that this entry points at.
     pub file_type: FileType,
     /// Field where clients can store state from within the The
@@ -41,10 +77,43 @@ pub struct DirEntry<C: ClientState> {
     /// OS dependent extended metadata
     pub read_metadata_ext: bool,
     pub metadata_ext: Option<MetaDataExt>,
+    /// Number of transient I/O errors that were retried while reading this
+    /// entry's metadata or opening it with `fs::read_dir`. See
+    /// [`retry_policy`](struct.WalkDirGeneric.html#method.retry_policy).
+    pub retries: usize,
+    /// True if [`dedupe_inodes`](struct.WalkDirGeneric.html#method.dedupe_inodes)
+    /// is enabled and the file or directory this entry points to was already
+    /// seen elsewhere in the walk. The contents of such directories are not
+    /// read.
+    pub duplicate_inode: bool,
+    /// Digest of the contents of regular files if
+    /// [`content_hash`](struct.WalkDirGeneric.html#method.content_hash) is
+    /// enabled. `None` for other entries and files above the size cutoff.
+    pub content_hash: Option<Vec<u8>>,
+    /// Error reading the contents of the file for `content_hash`.
+    pub content_hash_error: Option<Error>,
     // True if [`follow_links`] is `true` AND was created from a symlink path.
     follow_link: bool,
     // Origins of symlinks followed to get to this entry.
-    follow_link_ancestors: Arc<Vec<Arc<Path>>>,
+    follow_link_ancestors: Arc<Vec<Arc<FollowLinkAncestor>>>,
+    // Identity of the directory from the metadata read for this entry, passed
+    // on to detect symlink loops below it.
+    dir_inode_key: Option<(u64, u64)>,
+    // Partial `fold_dirs` value of this directory's children.
+    pub(crate) fold_value: Option<Box<dyn Any + Send>>,
+    // Position of this entry among the entries of its parent directory, the
+    // number of those entries, errors excluded, and the id derived from the
+    // index path. Set by `DirEntryIter` when yielding.
+    pub(crate) index_in_parent: usize,
+    pub(crate) sibling_count: usize,
+    pub(crate) entry_id: u64,
+    // True if the entry was yielded by the walk a checkpoint was taken from.
+    // Its contents are still read unless `read_children_path` is cleared.
+    pub(crate) skip_yield: bool,
+    // Remaining file names of a resume cursor below this directory.
+    pub(crate) resume_cursor: Option<Arc<[OsString]>>,
+    // True if `path` returns the relative path, see `RootStyle::Stripped`.
+    pub(crate) strip_root: bool,
 }
 
 impl<C: ClientState> DirEntry<C> {
@@ -54,17 +123,21 @@ impl<C: ClientState> DirEntry<C> {
         metadata: Option<MetaData>,
         metadata_ext: Option<MetaDataExt>,
         fs_dir_entry: &fs::DirEntry,
-        follow_link_ancestors: Arc<Vec<Arc<Path>>>,
+        follow_link_ancestors: Arc<Vec<Arc<FollowLinkAncestor>>>,
     ) -> Result<Self> {
         let file_type = fs_dir_entry
             .file_type()
             .map_err(|err| Error::from_path(depth, fs_dir_entry.path(), err))?;
-        let file_name = fs_dir_entry.file_name();
+        let file_name = FileName::from(fs_dir_entry.file_name());
         let read_children_path: Option<Arc<Path>> = if file_type.is_dir() {
-            Some(Arc::from(parent_path.join(&file_name)))
+            Some(shared_join_path(&parent_path, &file_name))
         } else {
             None
         };
+        let dir_inode_key = match metadata_ext.as_ref() {
+            Some(metadata_ext) if file_type.is_dir() => metadata_ext.inode_key(),
+            _ => None,
+        };
 
         Ok(DirEntry {
             depth,
@@ -78,8 +151,20 @@ impl<C: ClientState> DirEntry<C> {
             metadata,
             read_metadata_ext: metadata_ext.is_some(),
             metadata_ext,
+            retries: 0,
+            duplicate_inode: false,
+            content_hash: None,
+            content_hash_error: None,
             follow_link: false,
             follow_link_ancestors,
+            dir_inode_key,
+            fold_value: None,
+            index_in_parent: 0,
+            sibling_count: 0,
+            entry_id: 0,
+            skip_yield: false,
+            resume_cursor: None,
+            strip_root: false,
         })
     }
 
@@ -92,12 +177,38 @@ impl<C: ClientState> DirEntry<C> {
         follow_link: bool,
         follow_link_ancestors: Arc<Vec<Arc<Path>>>,
     ) -> Result<Self> {
-        let metadata = if follow_link {
-            fs::metadata(path).map_err(|err| Error::from_path(depth, path.to_owned(), err))?
-        } else {
-            fs::symlink_metadata(path)
-                .map_err(|err| Error::from_path(depth, path.to_owned(), err))?
-        };
+        let follow_link_ancestors = follow_link_ancestors
+            .iter()
+            .map(|ancestor| Arc::new(FollowLinkAncestor::new(ancestor.clone(), None)))
+            .collect();
+        Self::from_path_with_retry(
+            depth,
+            path,
+            read_metadata,
+            read_metadata_ext,
+            follow_link,
+            Arc::new(follow_link_ancestors),
+            &RetryPolicy::default(),
+        )
+    }
+
+    pub(crate) fn from_path_with_retry(
+        depth: usize,
+        path: &Path,
+        read_metadata: bool,
+        read_metadata_ext: bool,
+        follow_link: bool,
+        follow_link_ancestors: Arc<Vec<Arc<FollowLinkAncestor>>>,
+        retry_policy: &RetryPolicy,
+    ) -> Result<Self> {
+        let (metadata, retries) = retry_policy.run(|| {
+            if follow_link {
+                fs::metadata(path)
+            } else {
+                fs::symlink_metadata(path)
+            }
+        });
+        let metadata = metadata.map_err(|err| Error::from_path(depth, path.to_owned(), err))?;
 
         let root_name = path.file_name().unwrap_or(path.as_os_str());
 
@@ -106,6 +217,11 @@ impl<C: ClientState> DirEntry<C> {
         } else {
             None
         };
+        let dir_inode_key = if metadata.file_type().is_dir() {
+            get_metadata_ext(&metadata).inode_key()
+        } else {
+            None
+        };
 
         let entry_metadata;
         let entry_metadata_ext;
@@ -132,7 +248,7 @@ impl<C: ClientState> DirEntry<C> {
 
         Ok(DirEntry {
             depth,
-            file_name: root_name.to_owned(),
+            file_name: FileName::from(root_name),
             file_type: metadata.file_type(),
             parent_path: Arc::from(path.parent().map(Path::to_path_buf).unwrap_or_default()),
             read_children_path,
@@ -142,8 +258,20 @@ impl<C: ClientState> DirEntry<C> {
             metadata: entry_metadata,
             read_metadata_ext,
             metadata_ext: entry_metadata_ext,
+            retries,
+            duplicate_inode: false,
+            content_hash: None,
+            content_hash_error: None,
             follow_link,
             follow_link_ancestors,
+            dir_inode_key,
+            fold_value: None,
+            index_in_parent: 0,
+            sibling_count: 0,
+            entry_id: 0,
+            skip_yield: false,
+            resume_cursor: None,
+            strip_root: false,
         })
     }
 
@@ -176,11 +304,83 @@ impl<C: ClientState> DirEntry<C> {
         self.depth
     }
 
+    /// Position of this entry among the entries of its parent directory, as
+    /// they are yielded after sorting and
+    /// [`process_read_dir`](struct.WalkDirGeneric.html#method.process_read_dir).
+    /// For roots this is the position among the roots.
+    ///
+    /// Only yielded entries count: errors read from the parent directory and
+    /// entries held back by the entry predicates,
+    /// [`match_regex`](struct.WalkDirGeneric.html#method.match_regex) or
+    /// skipped when resuming from a checkpoint are not siblings. The value is
+    /// set when the entry is yielded by the iterator, it is `0` inside
+    /// `process_read_dir`.
+    pub fn index_in_parent(&self) -> usize {
+        self.index_in_parent
+    }
+
+    /// Number of entries of the parent directory, including this entry,
+    /// counted like
+    /// [`index_in_parent`](struct.DirEntry.html#method.index_in_parent). Set
+    /// when the entry is yielded by the iterator.
+    pub fn sibling_count(&self) -> usize {
+        self.sibling_count
+    }
+
+    /// Returns `true` if this is the last entry yielded from its parent
+    /// directory.
+    pub fn is_last_sibling(&self) -> bool {
+        self.index_in_parent + 1 == self.sibling_count
+    }
+
+    /// Identifier derived from the position of this entry in the walk, i.e.
+    /// the indices of the entry and all its ancestors.
+    ///
+    /// Walking an unchanged tree with the same options (in particular with
+    /// [`sort`](struct.WalkDirGeneric.html#method.sort) enabled) gives every
+    /// entry the same id again. Ids are 64 bit hashes, distinct entries of a
+    /// walk collide only with negligible probability. Set when the entry is
+    /// yielded by the iterator.
+    pub fn entry_id(&self) -> u64 {
+        self.entry_id
+    }
+
     /// Path to the file/directory represented by this entry.
     ///
-    /// The path is created by joining `parent_path` with `file_name`.
+    /// The path is created by joining `parent_path` with `file_name`. With
+    /// [`RootStyle::Stripped`](enum.RootStyle.html#variant.Stripped) it is the
+    /// [`relative_path`](struct.DirEntry.html#method.relative_path) instead.
     pub fn path(&self) -> PathBuf {
-        self.parent_path.join(&self.file_name)
+        if self.strip_root {
+            self.relative_path()
+        } else {
+            self.full_path()
+        }
+    }
+
+    /// Path to the file/directory relative to the root of the walk, empty for
+    /// the root itself.
+    ///
+    /// Built from the last `depth` components of the path, no system calls
+    /// are made and the root is not compared.
+    pub fn relative_path(&self) -> PathBuf {
+        if self.depth == 0 {
+            return PathBuf::new();
+        }
+        let mut relative_path: PathBuf =
+            relative_components(&self.parent_path, self.depth - 1).collect();
+        relative_path.push(&self.file_name);
+        relative_path
+    }
+
+    // Path used to access the file, independent of `RootStyle`.
+    pub(crate) fn full_path(&self) -> PathBuf {
+        join_path(&self.parent_path, &self.file_name)
+    }
+
+    // `full_path` as a shared path, used to read the children of the entry.
+    pub(crate) fn shared_full_path(&self) -> Arc<Path> {
+        shared_join_path(&self.parent_path, &self.file_name)
     }
 
     /// Returns `true` if and only if this entry was created from a symbolic
@@ -221,9 +421,9 @@ impl<C: ClientState> DirEntry<C> {
     /// [`std::fs::symlink_metadata`]: https://doc.rust-lang.org/stable/std/fs/fn.symlink_metadata.html
     pub fn metadata(&self) -> Result<fs::Metadata> {
         if self.follow_link {
-            fs::metadata(self.path())
+            fs::metadata(self.full_path())
         } else {
-            fs::symlink_metadata(self.path())
+            fs::symlink_metadata(self.full_path())
         }
         .map_err(|err| Error::from_entry(self, err))
     }
@@ -244,28 +444,44 @@ impl<C: ClientState> DirEntry<C> {
                 client_read_state,
                 path: read_children_path.clone(),
                 follow_link_ancestors: self.follow_link_ancestors.clone(),
+                inode_key: self.dir_inode_key,
+                resume_cursor: self.resume_cursor.clone(),
             })
     }
 
-    pub(crate) fn follow_symlink(&self) -> Result<Self> {
-        let path = self.path();
+    pub(crate) fn follow_symlink(&self, retry_policy: &RetryPolicy) -> Result<Self> {
+        let path = self.full_path();
         let origins = self.follow_link_ancestors.clone();
-        let dir_entry = DirEntry::from_path(
+        let mut dir_entry = DirEntry::from_path_with_retry(
             self.depth,
             &path,
             self.read_metadata,
             self.read_metadata_ext,
             true,
             origins,
+            retry_policy,
         )?;
+        dir_entry.retries += self.retries;
 
-        if dir_entry.file_type.is_dir() {
-            let target = fs::read_link(&path).map_err(|err| Error::from_io(self.depth, err))?;
+        // The root is its own first ancestor, only check entries below it.
+        if self.depth > 0 && dir_entry.file_type.is_dir() {
+            let inode_key = dir_entry.dir_inode_key;
+            // Fall back to comparing the link target with the ancestor paths
+            // when the filesystem doesn't provide file identities.
+            let target = match inode_key {
+                Some(_) => None,
+                None => Some(fs::read_link(&path).map_err(|err| Error::from_io(self.depth, err))?),
+            };
             for ancestor in self.follow_link_ancestors.iter().rev() {
-                if target.as_path() == ancestor.as_ref() {
+                let is_loop = match (inode_key, target.as_ref()) {
+                    (Some(inode_key), _) => ancestor.inode_key() == Some(inode_key),
+                    (None, Some(target)) => target.as_path() == ancestor.path.as_ref(),
+                    (None, None) => false,
+                };
+                if is_loop {
                     return Err(Error::from_loop(
                         self.depth,
-                        ancestor.as_ref(),
+                        ancestor.path.as_ref(),
                         path.as_ref(),
                     ));
                 }
@@ -276,6 +492,38 @@ impl<C: ClientState> DirEntry<C> {
     }
 }
 
+// `parent_path.join(file_name)` with a single allocation.
+fn join_path(parent_path: &Path, file_name: &OsStr) -> PathBuf {
+    let mut path = PathBuf::with_capacity(parent_path.as_os_str().len() + 1 + file_name.len());
+    path.push(parent_path);
+    path.push(file_name);
+    path
+}
+
+// `parent_path.join(file_name)` as a shared path. The path is joined in a
+// buffer reused by the thread, so that only the `Arc` is allocated.
+fn shared_join_path(parent_path: &Path, file_name: &OsStr) -> Arc<Path> {
+    thread_local! {
+        static BUFFER: RefCell<PathBuf> = const { RefCell::new(PathBuf::new()) };
+    }
+    BUFFER.with(|buffer| {
+        let mut buffer = buffer.borrow_mut();
+        buffer.as_mut_os_string().clear();
+        buffer.push(parent_path);
+        buffer.push(file_name);
+        Arc::from(buffer.as_path())
+    })
+}
+
+// The last `count` components of `path`. For a directory at depth `count`
+// these are the components below the root.
+pub(crate) fn relative_components(path: &Path, count: usize) -> impl Iterator<Item = &OsStr> {
+    let skip = path.components().count().saturating_sub(count);
+    path.components()
+        .skip(skip)
+        .map(|component| component.as_os_str())
+}
+
 impl<C: ClientState> fmt::Debug for DirEntry<C> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "DirEntry({:?})", self.path())
@@ -1,4 +1,5 @@
-use std::iter::Peekable;
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
 
 use super::*;
 use crate::Result;
@@ -9,15 +10,103 @@ use crate::Result;
 pub struct DirEntryIter<C: ClientState> {
     min_depth: usize,
     // iterator yielding next ReadDir results when needed
-    pub(crate) read_dir_iter: Option<Peekable<ReadDirIter<C>>>,
+    pub(crate) read_dir_iter: Option<ReadDirIter<C>>,
     // stack of ReadDir results, track location in filesystem traversal
-    read_dir_results_stack: Vec<vec::IntoIter<Result<DirEntry<C>>>>,
+    read_dir_results_stack: Vec<ReadDirFrame<C>>,
+    // index and file name per level of the last entry taken from the stack,
+    // valid up to position_len. Slots are reused to avoid allocations.
+    position: Vec<(usize, OsString)>,
+    position_len: usize,
+    // whether the results of the last yielded entry were pushed on the stack
+    children_pushed: bool,
+    // set if directories are taken in the order they were read
+    relaxed: Option<RelaxedState>,
+    // yield entries held back by predicates too, for FoldDirsIter
+    pub(crate) yield_held_back: bool,
+}
+
+// State of a walk with ResultOrdering::Relaxed. The stack holds at most the
+// results of one directory, other directories are matched with their parent
+// entry by index path.
+struct RelaxedState {
+    // entry_id of each yielded directory whose results are pending. Results
+    // of directories missing here were skipped and are dropped.
+    pending_dirs: HashMap<IndexPath, u64>,
+    // index path of the last yielded entry if its results are pending
+    last_dir: Option<IndexPath>,
+}
+
+// Results of a single ReadDir on the stack of DirEntryIter
+struct ReadDirFrame<C: ClientState> {
+    // number of results including errors
+    len: usize,
+    // number of entries that are yielded, the sibling count of the entries,
+    // and the number of them taken so far
+    yielded_len: usize,
+    yielded: usize,
+    // entry_id of the directory the results were read from
+    parent_id: u64,
+    results: vec::IntoIter<Result<DirEntry<C>>>,
+    // index path of the ReadDir and number of directories to read taken
+    // from it so far, only tracked in relaxed walks
+    index_path: IndexPath,
+    read_dir_count: usize,
+}
+
+impl<C: ClientState> ReadDirFrame<C> {
+    fn new(parent_id: u64, results_list: Vec<Result<DirEntry<C>>>) -> ReadDirFrame<C> {
+        ReadDirFrame {
+            len: results_list.len(),
+            yielded_len: yielded_count(&results_list),
+            yielded: 0,
+            parent_id,
+            results: results_list.into_iter(),
+            index_path: IndexPath::new(&[]),
+            read_dir_count: 0,
+        }
+    }
+
+    // Set the sibling position and id of the entry taken last from the
+    // results, returns its index among all results.
+    fn set_position(&mut self, dir_entry: &mut DirEntry<C>) -> usize {
+        let index = self.len - self.results.len() - 1;
+        if !dir_entry.skip_yield {
+            dir_entry.index_in_parent = self.yielded;
+            dir_entry.sibling_count = self.yielded_len;
+            self.yielded += 1;
+        }
+        dir_entry.entry_id = child_entry_id(self.parent_id, index);
+        index
+    }
+}
+
+// Number of entries of a directory that are yielded: errors and entries only
+// kept to descend into them don't count as siblings.
+pub(crate) fn yielded_count<C: ClientState>(results_list: &[Result<DirEntry<C>>]) -> usize {
+    results_list
+        .iter()
+        .filter(|result| result.as_ref().is_ok_and(|dir_entry| !dir_entry.skip_yield))
+        .count()
+}
+
+// Derive the id of the entry at `index` of the directory with `parent_id`.
+// Ids only depend on the index path, so they are stable across walks of an
+// unchanged tree as long as the order of entries is stable.
+pub(crate) fn child_entry_id(parent_id: u64, index: usize) -> u64 {
+    // splitmix64 finalizer over the combined parent id and index
+    let mut id = parent_id.wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ (index as u64 + 1);
+    id ^= id >> 30;
+    id = id.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    id ^= id >> 27;
+    id = id.wrapping_mul(0x94d0_49bb_1331_11eb);
+    id ^ (id >> 31)
 }
 
 impl<C: ClientState> DirEntryIter<C> {
     pub(crate) fn new(
         root_entry_results: Vec<Result<DirEntry<C>>>,
         parallelism: Parallelism,
+        ordering: ResultOrdering,
         min_depth: usize,
         root_read_dir_state: C::ReadDirState,
         core_read_dir_callback: Arc<ReadDirCallback<C>>,
@@ -34,51 +123,227 @@ impl<C: ClientState> DirEntryIter<C> {
             .collect();
 
         // 2. Init new read_dir_iter from those specs
-        let read_dir_iter =
-            ReadDirIter::try_new(read_dir_specs, parallelism, core_read_dir_callback)
-                .map(|iter| iter.peekable());
+        let relaxed = match parallelism {
+            Parallelism::Serial => None,
+            _ => (ordering == ResultOrdering::Relaxed).then(|| RelaxedState {
+                pending_dirs: HashMap::new(),
+                last_dir: None,
+            }),
+        };
+        let read_dir_iter = ReadDirIter::try_new(
+            read_dir_specs,
+            parallelism,
+            ordering,
+            core_read_dir_callback,
+        );
 
         // 3. Return DirEntryIter that will return initial root entries and then
         //    fill and process read_dir_iter until complete
         DirEntryIter {
             min_depth,
             read_dir_iter,
-            read_dir_results_stack: vec![root_entry_results.into_iter()],
+            read_dir_results_stack: vec![ReadDirFrame::new(0, root_entry_results)],
+            position: Vec::new(),
+            position_len: 0,
+            children_pushed: false,
+            relaxed,
+            yield_held_back: false,
         }
     }
 
+    /// Skip the rest of the directory yielded last, like walkdir's
+    /// `skip_current_dir`.
+    ///
+    /// If the last entry was a directory its contents are skipped, otherwise
+    /// the remaining entries of its parent directory are skipped. Results
+    /// already read for the skipped entries are dropped and pending reads of
+    /// their subdirectories are cancelled.
+    /// With [`ResultOrdering::Relaxed`](enum.ResultOrdering.html#variant.Relaxed)
+    /// the reads are not cancelled, their results are dropped when they
+    /// arrive.
+    pub fn skip_current_dir(&mut self) {
+        if !self.skip_pending_dir() {
+            self.pop_read_dir_results();
+        }
+    }
+
+    /// Skip the contents of the last yielded entry if it is a directory.
+    /// Unlike [`skip_current_dir`](#method.skip_current_dir) this does
+    /// nothing for other entries.
+    pub fn skip_entry_children(&mut self) {
+        if self.children_pushed {
+            self.pop_read_dir_results();
+        } else {
+            self.skip_pending_dir();
+        }
+    }
+
+    // In relaxed walks, forget the last yielded directory so that its results
+    // and those of its descendants are dropped when they arrive. Returns
+    // false if the last entry has no pending results.
+    fn skip_pending_dir(&mut self) -> bool {
+        let Some(relaxed) = self.relaxed.as_mut() else {
+            return false;
+        };
+        let Some(last_dir) = relaxed.last_dir.take() else {
+            return false;
+        };
+        relaxed.pending_dirs.remove(&last_dir);
+        true
+    }
+
+    // Drop the results on top of the stack and everything below the
+    // directories among them.
+    fn pop_read_dir_results(&mut self) {
+        self.children_pushed = false;
+        let Some(read_dir_results) = self.read_dir_results_stack.pop() else {
+            return;
+        };
+        if self.relaxed.is_some() {
+            // Directories not yielded yet were never added to pending_dirs
+            return;
+        }
+        let skipped_read_dirs = read_dir_results
+            .results
+            .filter(|result| {
+                result
+                    .as_ref()
+                    .is_ok_and(|dir_entry| dir_entry.read_children_path.is_some())
+            })
+            .count();
+        if let Some(read_dir_iter) = self.read_dir_iter.as_mut() {
+            read_dir_iter.skip_read_dirs(skipped_read_dirs);
+        }
+    }
+
+    /// Position of the last entry of the walk, to resume the walk later with
+    /// [`WalkDirGeneric::resume_from`](struct.WalkDirGeneric.html#method.resume_from).
+    ///
+    /// Returns `None` if no entry was yielded yet or if the walk uses
+    /// [`ResultOrdering::Relaxed`](enum.ResultOrdering.html#variant.Relaxed).
+    /// Entries skipped because of
+    /// [`min_depth`](struct.WalkDirGeneric.html#method.min_depth) count as
+    /// yielded. Errors are not tracked, errors following the last entry are
+    /// yielded again when resuming.
+    pub fn checkpoint(&self) -> Option<Checkpoint> {
+        let (root, _) = self.position[..self.position_len].first()?;
+        let below_root = &self.position[1..self.position_len];
+        Some(Checkpoint {
+            root: *root,
+            names: below_root.iter().map(|(_, name)| name.clone()).collect(),
+        })
+    }
+
+    fn update_position(&mut self, level: usize, index: usize, file_name: &OsStr) {
+        if let Some((slot_index, slot_name)) = self.position.get_mut(level) {
+            *slot_index = index;
+            slot_name.clear();
+            slot_name.push(file_name);
+        } else {
+            self.position.push((index, file_name.to_owned()));
+        }
+        self.position_len = level + 1;
+    }
+
     fn push_next_read_dir_results(
-        iter: &mut Peekable<ReadDirIter<C>>,
-        results: &mut Vec<vec::IntoIter<Result<DirEntry<C>>>>,
+        iter: &mut ReadDirIter<C>,
+        results: &mut Vec<ReadDirFrame<C>>,
+        dir_entry: &mut DirEntry<C>,
     ) -> Result<()> {
         // Push next read dir results or return error if read failed
-        let read_dir_result = iter.next().unwrap();
-        let read_dir = match read_dir_result {
-            Ok(read_dir) => read_dir,
-            Err(err) => return Err(err),
-        };
+        let read_dir = iter.next().unwrap()?;
 
-        let ReadDir { results_list, .. } = read_dir;
-        results.push(results_list.into_iter());
+        let ReadDir {
+            results_list,
+            retries,
+            fold_value,
+            ..
+        } = read_dir;
+        results.push(ReadDirFrame::new(dir_entry.entry_id, results_list));
+        dir_entry.retries += retries;
+        dir_entry.fold_value = fold_value;
 
         Ok(())
     }
+
+    // Next entry of a relaxed walk: the entries of the ReadDir on the stack,
+    // then those of the next ReadDir of the result queue.
+    fn next_relaxed(&mut self) -> Option<Result<DirEntry<C>>> {
+        loop {
+            let relaxed = self.relaxed.as_mut()?;
+            let Some(top_read_dir_results) = self.read_dir_results_stack.last_mut() else {
+                let Ordered {
+                    value: read_dir_result,
+                    index_path,
+                    ..
+                } = self.read_dir_iter.as_mut()?.next_ordered()?;
+                let Some(parent_id) = relaxed.pending_dirs.remove(&index_path) else {
+                    continue;
+                };
+                let read_dir = match read_dir_result {
+                    Ok(read_dir) => read_dir,
+                    Err(err) => return Some(Err(err)),
+                };
+                let mut frame = ReadDirFrame::new(parent_id, read_dir.results_list);
+                frame.index_path = index_path;
+                self.read_dir_results_stack.push(frame);
+                continue;
+            };
+
+            let Some(dir_entry_result) = top_read_dir_results.results.next() else {
+                self.read_dir_results_stack.pop();
+                continue;
+            };
+            relaxed.last_dir = None;
+            let mut dir_entry = match dir_entry_result {
+                Ok(dir_entry) => dir_entry,
+                Err(err) => return Some(Err(err)),
+            };
+            top_read_dir_results.set_position(&mut dir_entry);
+            if dir_entry.read_children_path.is_some() {
+                if self.read_dir_iter.is_none() {
+                    return Some(Err(Error::busy()));
+                }
+                // Same index path as given to the spec by the worker
+                let index_path = top_read_dir_results
+                    .index_path
+                    .adding(top_read_dir_results.read_dir_count);
+                top_read_dir_results.read_dir_count += 1;
+                relaxed
+                    .pending_dirs
+                    .insert(index_path.clone(), dir_entry.entry_id);
+                relaxed.last_dir = Some(index_path);
+            }
+
+            if dir_entry.depth >= self.min_depth && (!dir_entry.skip_yield || self.yield_held_back)
+            {
+                return Some(Ok(dir_entry));
+            }
+        }
+    }
 }
 
 impl<C: ClientState> Iterator for DirEntryIter<C> {
     type Item = Result<DirEntry<C>>;
     fn next(&mut self) -> Option<Self::Item> {
+        if self.relaxed.is_some() {
+            return self.next_relaxed();
+        }
         loop {
             // 1. Get current read dir results iter from top of stack
+            let level = self.read_dir_results_stack.len().checked_sub(1)?;
             let top_read_dir_results = self.read_dir_results_stack.last_mut()?;
 
             // 2. If more results in current read dir then process
-            if let Some(dir_entry_result) = top_read_dir_results.next() {
+            if let Some(dir_entry_result) = top_read_dir_results.results.next() {
                 // 2.1 Handle error case
+                self.children_pushed = false;
                 let mut dir_entry = match dir_entry_result {
                     Ok(dir_entry) => dir_entry,
                     Err(err) => return Some(Err(err)),
                 };
+                let index = top_read_dir_results.set_position(&mut dir_entry);
+                self.update_position(level, index, &dir_entry.file_name);
                 // 2.2 If dir_entry has a read_children_path means we need to read a new
                 // directory and push those results onto read_dir_results_stack
                 if dir_entry.read_children_path.is_some() {
@@ -86,14 +351,19 @@ impl<C: ClientState> Iterator for DirEntryIter<C> {
                         Ok(iter) => iter,
                         Err(err) => return Some(Err(err)),
                     };
-                    if let Err(err) =
-                        Self::push_next_read_dir_results(iter, &mut self.read_dir_results_stack)
-                    {
-                        dir_entry.read_children_error = Some(err);
+                    match Self::push_next_read_dir_results(
+                        iter,
+                        &mut self.read_dir_results_stack,
+                        &mut dir_entry,
+                    ) {
+                        Ok(()) => self.children_pushed = true,
+                        Err(err) => dir_entry.read_children_error = Some(err),
                     }
                 }
 
-                if dir_entry.depth >= self.min_depth {
+                if dir_entry.depth >= self.min_depth
+                    && (!dir_entry.skip_yield || self.yield_held_back)
+                {
                     // 2.3 Finished, return dir_entry
                     return Some(Ok(dir_entry));
                 }
@@ -0,0 +1,135 @@
+use std::fs;
+#[cfg(unix)]
+use std::os::unix::fs::FileTypeExt;
+use std::time::SystemTime;
+
+use super::*;
+
+/// Type of an entry, see
+/// [`WalkDirGeneric::file_types`](struct.WalkDirGeneric.html#method.file_types).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EntryType {
+    Dir,
+    File,
+    Symlink,
+    /// Block device, Unix only.
+    BlockDevice,
+    /// Character device, Unix only.
+    CharDevice,
+    /// Named pipe, Unix only.
+    Fifo,
+    /// Unix domain socket, Unix only.
+    Socket,
+}
+
+impl EntryType {
+    fn matches(self, file_type: &fs::FileType) -> bool {
+        match self {
+            EntryType::Dir => file_type.is_dir(),
+            EntryType::File => file_type.is_file(),
+            EntryType::Symlink => file_type.is_symlink(),
+            #[cfg(unix)]
+            EntryType::BlockDevice => file_type.is_block_device(),
+            #[cfg(unix)]
+            EntryType::CharDevice => file_type.is_char_device(),
+            #[cfg(unix)]
+            EntryType::Fifo => file_type.is_fifo(),
+            #[cfg(unix)]
+            EntryType::Socket => file_type.is_socket(),
+            #[cfg(not(unix))]
+            _ => false,
+        }
+    }
+}
+
+// Declarative predicates of WalkDirGeneric, all of them must match for an
+// entry to be yielded.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct EntryFilter {
+    pub(crate) file_types: Option<Vec<EntryType>>,
+    pub(crate) min_size: Option<u64>,
+    pub(crate) max_size: Option<u64>,
+    pub(crate) modified_after: Option<SystemTime>,
+    pub(crate) modified_before: Option<SystemTime>,
+    #[cfg(unix)]
+    pub(crate) owned_by: Option<u32>,
+    #[cfg(unix)]
+    pub(crate) permission_mask: Option<u32>,
+}
+
+impl EntryFilter {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.file_types.is_none() && !self.needs_metadata()
+    }
+
+    pub(crate) fn needs_metadata(&self) -> bool {
+        self.min_size.is_some()
+            || self.max_size.is_some()
+            || self.modified_after.is_some()
+            || self.modified_before.is_some()
+            || self.needs_metadata_ext()
+    }
+
+    pub(crate) fn needs_metadata_ext(&self) -> bool {
+        #[cfg(unix)]
+        {
+            self.owned_by.is_some() || self.permission_mask.is_some()
+        }
+        #[cfg(not(unix))]
+        {
+            false
+        }
+    }
+
+    // Entries whose metadata is needed but couldn't be read don't match.
+    pub(crate) fn matches<C: ClientState>(&self, dir_entry: &DirEntry<C>) -> bool {
+        if let Some(file_types) = self.file_types.as_ref() {
+            if !file_types
+                .iter()
+                .any(|entry_type| entry_type.matches(&dir_entry.file_type))
+            {
+                return false;
+            }
+        }
+        if self.needs_metadata() {
+            let Some(metadata) = dir_entry.metadata.as_ref() else {
+                return false;
+            };
+            if self
+                .min_size
+                .is_some_and(|min_size| metadata.size < min_size)
+                || self
+                    .max_size
+                    .is_some_and(|max_size| metadata.size > max_size)
+            {
+                return false;
+            }
+            if self.modified_after.is_some() || self.modified_before.is_some() {
+                let Some(modified) = metadata.modified else {
+                    return false;
+                };
+                if self.modified_after.is_some_and(|after| modified <= after)
+                    || self
+                        .modified_before
+                        .is_some_and(|before| modified >= before)
+                {
+                    return false;
+                }
+            }
+        }
+        #[cfg(unix)]
+        if self.needs_metadata_ext() {
+            let Some(metadata_ext) = dir_entry.metadata_ext.as_ref() else {
+                return false;
+            };
+            if self.owned_by.is_some_and(|uid| metadata_ext.st_uid != uid)
+                || self
+                    .permission_mask
+                    .is_some_and(|mask| metadata_ext.st_mode & mask != mask)
+            {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -11,10 +11,10 @@ use crate::{ClientState, DirEntry};
 /// particular, it adds the following information:
 ///
 /// * The depth at which the error occurred in the file tree, relative to the
-/// root.
+///   root.
 /// * The path, if any, associated with the IO error.
 /// * An indication that a loop occurred when following symbolic links. In this
-/// case, there is no underlying IO error.
+///   case, there is no underlying IO error.
 ///
 /// To maintain good ergonomics, this type has a
 /// [`impl From<Error> for std::io::Error`][impl] defined which preserves the original context.
@@ -27,7 +27,8 @@ use crate::{ClientState, DirEntry};
 #[derive(Debug)]
 pub struct Error {
     depth: usize,
-    inner: ErrorInner,
+    // Boxed to keep the optional errors of `DirEntry` small
+    inner: Box<ErrorInner>,
 }
 
 #[derive(Debug)]
@@ -51,7 +52,7 @@ impl Error {
     ///
     /// [`std::fs::read_dir`]: https://doc.rust-lang.org/stable/std/fs/fn.read_dir.html
     pub fn path(&self) -> Option<&Path> {
-        match self.inner {
+        match *self.inner {
             ErrorInner::ThreadpoolBusy => None,
             ErrorInner::Io { path: None, .. } => None,
             ErrorInner::Io {
@@ -75,7 +76,7 @@ impl Error {
     /// [`None`]: https://doc.rust-lang.org/stable/std/option/enum.Option.html#variant.None
     /// [`path`]: struct.Error.html#path
     pub fn loop_ancestor(&self) -> Option<&Path> {
-        match self.inner {
+        match *self.inner {
             ErrorInner::Loop { ref ancestor, .. } => Some(ancestor),
             _ => None,
         }
@@ -105,7 +106,7 @@ impl Error {
     ///
     /// > This is the original [`io::Error`] and is _not_ the same as
     /// > [`impl From<Error> for std::io::Error`][impl] which contains additional context about the
-    /// error.
+    /// > error.
     ///
     /// # Example
     ///
@@ -152,7 +153,7 @@ impl Error {
     /// [`into_io_error`]: struct.Error.html#method.into_io_error
     /// [impl]: struct.Error.html#impl-From%3CError%3E
     pub fn io_error(&self) -> Option<&io::Error> {
-        match self.inner {
+        match *self.inner {
             ErrorInner::Io { ref err, .. } => Some(err),
             _ => None,
         }
@@ -162,7 +163,7 @@ impl Error {
     ///
     /// Note that business detection is timeout based, and we don't know if it would have been a deadlock or not.
     pub fn is_busy(&self) -> bool {
-        matches!(self.inner, ErrorInner::ThreadpoolBusy)
+        matches!(*self.inner, ErrorInner::ThreadpoolBusy)
     }
 
     /// Similar to [`io_error`] except consumes self to convert to the original
@@ -171,7 +172,7 @@ impl Error {
     /// [`io_error`]: struct.Error.html#method.io_error
     /// [`io::Error`]: https://doc.rust-lang.org/stable/std/io/struct.Error.html
     pub fn into_io_error(self) -> Option<io::Error> {
-        match self.inner {
+        match *self.inner {
             ErrorInner::Io { err, .. } => Some(err),
             _ => None,
         }
@@ -180,50 +181,50 @@ impl Error {
     pub(crate) fn busy() -> Self {
         Error {
             depth: 0,
-            inner: ErrorInner::ThreadpoolBusy,
+            inner: Box::new(ErrorInner::ThreadpoolBusy),
         }
     }
     pub(crate) fn from_path(depth: usize, pb: PathBuf, err: io::Error) -> Self {
         Error {
             depth,
-            inner: ErrorInner::Io {
+            inner: Box::new(ErrorInner::Io {
                 path: Some(pb),
                 err,
-            },
+            }),
         }
     }
 
     pub(crate) fn from_entry<C: ClientState>(dent: &DirEntry<C>, err: io::Error) -> Self {
         Error {
             depth: dent.depth(),
-            inner: ErrorInner::Io {
+            inner: Box::new(ErrorInner::Io {
                 path: Some(dent.path()),
                 err,
-            },
+            }),
         }
     }
 
     pub(crate) fn from_io(depth: usize, err: io::Error) -> Self {
         Error {
             depth,
-            inner: ErrorInner::Io { path: None, err },
+            inner: Box::new(ErrorInner::Io { path: None, err }),
         }
     }
 
     pub(crate) fn from_loop(depth: usize, ancestor: &Path, child: &Path) -> Self {
         Error {
             depth,
-            inner: ErrorInner::Loop {
+            inner: Box::new(ErrorInner::Loop {
                 ancestor: ancestor.to_path_buf(),
                 child: child.to_path_buf(),
-            },
+            }),
         }
     }
 }
 
 impl error::Error for Error {
     fn source(&self) -> Option<&(dyn error::Error + 'static)> {
-        match self.inner {
+        match *self.inner {
             ErrorInner::Io { ref err, .. } => Some(err),
             ErrorInner::Loop { .. } | ErrorInner::ThreadpoolBusy => None,
         }
@@ -231,7 +232,7 @@ impl error::Error for Error {
 
     #[allow(deprecated)]
     fn description(&self) -> &str {
-        match self.inner {
+        match *self.inner {
             ErrorInner::Io { ref err, .. } => err.description(),
             ErrorInner::Loop { .. } => "file system loop found",
             ErrorInner::ThreadpoolBusy => "thread-pool busy",
@@ -245,7 +246,7 @@ impl error::Error for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self.inner {
+        match *self.inner {
             ErrorInner::ThreadpoolBusy => f.write_str("rayon thread-pool too busy or dependency loop detected - aborting before possibility of deadlock"),
             ErrorInner::Io {
                 path: None,
@@ -282,19 +283,10 @@ impl From<Error> for io::Error {
     /// ["inner error"]: https://doc.rust-lang.org/std/io/struct.Error.html#method.into_inner
     /// [`into_io_error`]: struct.WalkDir.html#method.into_io_error
     fn from(walk_err: Error) -> io::Error {
-        let kind = match walk_err {
-            Error {
-                inner: ErrorInner::Io { ref err, .. },
-                ..
-            } => err.kind(),
-            Error {
-                inner: ErrorInner::Loop { .. },
-                ..
-            } => io::ErrorKind::Other,
-            Error {
-                inner: ErrorInner::ThreadpoolBusy,
-                ..
-            } => io::ErrorKind::Other,
+        let kind = match *walk_err.inner {
+            ErrorInner::Io { ref err, .. } => err.kind(),
+            ErrorInner::Loop { .. } => io::ErrorKind::Other,
+            ErrorInner::ThreadpoolBusy => io::ErrorKind::Other,
         };
         io::Error::new(kind, walk_err)
     }
@@ -0,0 +1,170 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::ops::Deref;
+use std::path::Path;
+
+// Longest name stored without allocation, keeps `FileName` as small as an
+// `OsString`.
+const INLINE_CAPACITY: usize = 22;
+
+/// File name of a [`DirEntry`](struct.DirEntry.html).
+///
+/// Most file names are short, names of up to 22 bytes are stored inline
+/// without a heap allocation. Longer names are stored in an allocation of
+/// exactly their length. Dereferences to `OsStr`.
+#[derive(Clone)]
+pub struct FileName(Repr);
+
+#[derive(Clone)]
+enum Repr {
+    Inline {
+        len: u8,
+        bytes: [u8; INLINE_CAPACITY],
+    },
+    Heap(Box<OsStr>),
+}
+
+impl FileName {
+    /// The name as `OsStr`.
+    pub fn as_os_str(&self) -> &OsStr {
+        match &self.0 {
+            Repr::Inline { len, bytes } => {
+                // Safety: the bytes were copied from a complete `OsStr` by
+                // `From<&OsStr>`
+                unsafe { OsStr::from_encoded_bytes_unchecked(&bytes[..usize::from(*len)]) }
+            }
+            Repr::Heap(name) => name,
+        }
+    }
+
+    /// Convert into an `OsString`.
+    pub fn into_os_string(self) -> OsString {
+        match self.0 {
+            Repr::Inline { .. } => self.as_os_str().to_owned(),
+            Repr::Heap(name) => name.into_os_string(),
+        }
+    }
+}
+
+impl From<&OsStr> for FileName {
+    fn from(name: &OsStr) -> FileName {
+        let encoded = name.as_encoded_bytes();
+        if encoded.len() <= INLINE_CAPACITY {
+            let mut bytes = [0; INLINE_CAPACITY];
+            bytes[..encoded.len()].copy_from_slice(encoded);
+            FileName(Repr::Inline {
+                len: encoded.len() as u8,
+                bytes,
+            })
+        } else {
+            FileName(Repr::Heap(Box::from(name)))
+        }
+    }
+}
+
+impl From<OsString> for FileName {
+    fn from(name: OsString) -> FileName {
+        if name.len() <= INLINE_CAPACITY {
+            FileName::from(name.as_os_str())
+        } else {
+            FileName(Repr::Heap(name.into_boxed_os_str()))
+        }
+    }
+}
+
+impl From<&str> for FileName {
+    fn from(name: &str) -> FileName {
+        FileName::from(OsStr::new(name))
+    }
+}
+
+impl From<FileName> for OsString {
+    fn from(name: FileName) -> OsString {
+        name.into_os_string()
+    }
+}
+
+impl Deref for FileName {
+    type Target = OsStr;
+
+    fn deref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<OsStr> for FileName {
+    fn as_ref(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl AsRef<Path> for FileName {
+    fn as_ref(&self) -> &Path {
+        Path::new(self.as_os_str())
+    }
+}
+
+impl Borrow<OsStr> for FileName {
+    fn borrow(&self) -> &OsStr {
+        self.as_os_str()
+    }
+}
+
+impl PartialEq for FileName {
+    fn eq(&self, other: &FileName) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl Eq for FileName {}
+
+impl PartialEq<OsStr> for FileName {
+    fn eq(&self, other: &OsStr) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl PartialEq<OsString> for FileName {
+    fn eq(&self, other: &OsString) -> bool {
+        self.as_os_str() == other.as_os_str()
+    }
+}
+
+impl PartialEq<str> for FileName {
+    fn eq(&self, other: &str) -> bool {
+        self.as_os_str() == other
+    }
+}
+
+impl PartialEq<&str> for FileName {
+    fn eq(&self, other: &&str) -> bool {
+        self.as_os_str() == *other
+    }
+}
+
+impl PartialOrd for FileName {
+    fn partial_cmp(&self, other: &FileName) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for FileName {
+    fn cmp(&self, other: &FileName) -> Ordering {
+        self.as_os_str().cmp(other.as_os_str())
+    }
+}
+
+impl Hash for FileName {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_os_str().hash(state)
+    }
+}
+
+impl fmt::Debug for FileName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.as_os_str().fmt(f)
+    }
+}
@@ -0,0 +1,94 @@
+use std::collections::VecDeque;
+
+use super::*;
+use crate::Result;
+
+type InitFunction<T> = dyn Fn() -> T + Send + Sync;
+
+type MergeFunction<T> = dyn FnMut(&mut T, &T);
+
+/// Iterator returned by
+/// [`WalkDirGeneric::fold_dirs`](struct.WalkDirGeneric.html#method.fold_dirs).
+///
+/// Yields every directory together with the value folded from its subtree,
+/// as soon as the subtree is complete. Directories are yielded after all of
+/// their descendants, the root comes last. Directories held back by entry
+/// predicates are yielded as well, they still collect the values of their
+/// contents. Roots that aren't directories are yielded with the initial
+/// value.
+pub struct FoldDirsIter<C: ClientState, T> {
+    dir_entry_iter: DirEntryIter<C>,
+    init: Arc<InitFunction<T>>,
+    merge: Box<MergeFunction<T>>,
+    // Directories whose subtree is still being walked, deepest last
+    stack: Vec<(DirEntry<C>, T)>,
+    // Completed directories and errors waiting to be yielded
+    ready: VecDeque<Result<(DirEntry<C>, T)>>,
+}
+
+impl<C: ClientState, T: 'static> FoldDirsIter<C, T> {
+    pub(crate) fn new(
+        mut dir_entry_iter: DirEntryIter<C>,
+        init: Arc<InitFunction<T>>,
+        merge: Box<MergeFunction<T>>,
+    ) -> FoldDirsIter<C, T> {
+        dir_entry_iter.yield_held_back = true;
+        FoldDirsIter {
+            dir_entry_iter,
+            init,
+            merge,
+            stack: Vec::new(),
+            ready: VecDeque::new(),
+        }
+    }
+
+    // Complete all directories that can't receive further descendants once an
+    // entry at `depth` is seen.
+    fn complete_dirs(&mut self, depth: usize) {
+        while self
+            .stack
+            .last()
+            .is_some_and(|(dir_entry, _)| dir_entry.depth >= depth)
+        {
+            let (dir_entry, value) = self.stack.pop().unwrap();
+            if let Some((_, parent_value)) = self.stack.last_mut() {
+                (self.merge)(parent_value, &value);
+            }
+            self.ready.push_back(Ok((dir_entry, value)));
+        }
+    }
+}
+
+impl<C: ClientState, T: 'static> Iterator for FoldDirsIter<C, T> {
+    type Item = Result<(DirEntry<C>, T)>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ready) = self.ready.pop_front() {
+                return Some(ready);
+            }
+            match self.dir_entry_iter.next() {
+                Some(Ok(mut dir_entry)) => {
+                    self.complete_dirs(dir_entry.depth);
+                    if dir_entry.read_children_path.is_some() {
+                        let value = dir_entry
+                            .fold_value
+                            .take()
+                            .and_then(|value| value.downcast::<T>().ok())
+                            .map(|value| *value)
+                            .unwrap_or_else(|| (self.init)());
+                        self.stack.push((dir_entry, value));
+                    } else if dir_entry.depth == 0 && !dir_entry.skip_yield {
+                        self.ready.push_back(Ok((dir_entry, (self.init)())));
+                    }
+                }
+                Some(Err(err)) => self.ready.push_back(Err(err)),
+                None => {
+                    if self.stack.is_empty() {
+                        return None;
+                    }
+                    self.complete_dirs(0);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+#[cfg(windows)]
+use std::os::windows::fs::MetadataExt;
+use std::sync::Arc;
+
+#[cfg(windows)]
+use winapi::um::winnt::{FILE_ATTRIBUTE_HIDDEN, FILE_ATTRIBUTE_SYSTEM};
+
+/// Decides which entries are hidden, see
+/// [`WalkDirGeneric::skip_hidden_by`](struct.WalkDirGeneric.html#method.skip_hidden_by).
+///
+/// The predicate is evaluated on the raw directory listing, before metadata
+/// is read, so hidden entries cost neither a `stat` call nor a `DirEntry`.
+#[derive(Clone, Default)]
+pub enum Hidden {
+    /// Names starting with a dot.
+    Dotfiles,
+    /// Entries with `FILE_ATTRIBUTE_HIDDEN` or `FILE_ATTRIBUTE_SYSTEM` on
+    /// Windows, read from the directory listing. Nothing is hidden on other
+    /// platforms.
+    OsAttribute,
+    /// Dotfiles and entries with the Windows hidden or system attribute.
+    Both,
+    /// Nothing is hidden.
+    #[default]
+    None,
+    /// Entries whose file name matches the predicate.
+    Custom(Arc<dyn Fn(&OsStr) -> bool + Send + Sync>),
+}
+
+impl Hidden {
+    pub(crate) fn is_hidden(&self, fs_dir_entry: &fs::DirEntry) -> bool {
+        match self {
+            Hidden::Dotfiles => is_dotfile(&fs_dir_entry.file_name()),
+            Hidden::OsAttribute => has_hidden_attribute(fs_dir_entry),
+            Hidden::Both => {
+                is_dotfile(&fs_dir_entry.file_name()) || has_hidden_attribute(fs_dir_entry)
+            }
+            Hidden::None => false,
+            Hidden::Custom(predicate) => predicate(&fs_dir_entry.file_name()),
+        }
+    }
+}
+
+impl fmt::Debug for Hidden {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Hidden::Dotfiles => write!(f, "Dotfiles"),
+            Hidden::OsAttribute => write!(f, "OsAttribute"),
+            Hidden::Both => write!(f, "Both"),
+            Hidden::None => write!(f, "None"),
+            Hidden::Custom(_) => write!(f, "Custom(..)"),
+        }
+    }
+}
+
+fn is_dotfile(file_name: &OsStr) -> bool {
+    file_name.as_encoded_bytes().first() == Some(&b'.')
+}
+
+// On Windows the metadata of a directory entry comes with the directory
+// listing, no extra call is made.
+#[cfg(windows)]
+fn has_hidden_attribute(fs_dir_entry: &fs::DirEntry) -> bool {
+    fs_dir_entry.metadata().is_ok_and(|metadata| {
+        metadata.file_attributes() & (FILE_ATTRIBUTE_HIDDEN | FILE_ATTRIBUTE_SYSTEM) != 0
+    })
+}
+
+#[cfg(not(windows))]
+fn has_hidden_attribute(_fs_dir_entry: &fs::DirEntry) -> bool {
+    false
+}
@@ -1,54 +1,113 @@
+use smallvec::SmallVec;
 use std::cmp::Ordering;
+use std::fmt;
 
-#[derive(Clone, Debug)]
+// Indices up to this value are encoded as a single byte. Larger indices are
+// encoded as a length byte `SHORT_MAX + n` followed by `n` big endian bytes.
+// Longer encodings start with a larger byte, so comparing the encoded bytes
+// compares the index paths lexicographically.
+const SHORT_MAX: u8 = 247;
+
+/// Position of a directory in the walk: the indices of the directory and its
+/// ancestors among the directories read from their parents.
+///
+/// Stored as encoded bytes, inline for paths of up to 16 small indices, so
+/// that paths are compared with `memcmp` and children are created without
+/// allocating. The offset of every index is kept as well, so that the last
+/// index is popped or incremented without decoding the whole path.
+#[derive(Clone, PartialEq, Eq, Hash)]
 pub struct IndexPath {
-    pub indices: Vec<usize>,
+    bytes: SmallVec<[u8; 16]>,
+    // start of each index in bytes, determined by bytes
+    starts: SmallVec<[u32; 8]>,
 }
 
 impl IndexPath {
-    pub fn new(indices: Vec<usize>) -> IndexPath {
-        IndexPath { indices }
+    pub fn new(indices: &[usize]) -> IndexPath {
+        let mut index_path = IndexPath {
+            bytes: SmallVec::new(),
+            starts: SmallVec::new(),
+        };
+        for index in indices {
+            index_path.push(*index);
+        }
+        index_path
     }
 
     pub fn adding(&self, index: usize) -> IndexPath {
-        let mut indices = self.indices.clone();
-        indices.push(index);
-        IndexPath::new(indices)
+        let mut index_path = self.clone();
+        index_path.push(index);
+        index_path
     }
 
     pub fn push(&mut self, index: usize) {
-        self.indices.push(index);
+        self.starts.push(self.bytes.len() as u32);
+        if index <= usize::from(SHORT_MAX) {
+            self.bytes.push(index as u8);
+        } else {
+            let be_bytes = (index as u64).to_be_bytes();
+            let skip = (index as u64).leading_zeros() as usize / 8;
+            self.bytes.push(SHORT_MAX + (8 - skip) as u8);
+            self.bytes.extend_from_slice(&be_bytes[skip..]);
+        }
     }
 
     pub fn increment_last(&mut self) {
-        *self.indices.last_mut().unwrap() += 1;
+        let last = self.pop().unwrap();
+        self.push(last + 1);
     }
 
     pub fn pop(&mut self) -> Option<usize> {
-        self.indices.pop()
+        let start = self.starts.pop()? as usize;
+        let index = self.decode(start);
+        self.bytes.truncate(start);
+        Some(index)
     }
 
     pub fn is_empty(&self) -> bool {
-        self.indices.is_empty()
+        self.bytes.is_empty()
+    }
+
+    /// Returns `true` if `self` comes before `other` in depth first order.
+    pub fn precedes(&self, other: &IndexPath) -> bool {
+        self.bytes < other.bytes
     }
-}
 
-impl PartialEq for IndexPath {
-    fn eq(&self, o: &Self) -> bool {
-        self.indices.eq(&o.indices)
+    pub fn indices(&self) -> Vec<usize> {
+        self.starts
+            .iter()
+            .map(|start| self.decode(*start as usize))
+            .collect()
+    }
+
+    // Decode the index starting at `start`.
+    fn decode(&self, start: usize) -> usize {
+        let first = self.bytes[start];
+        if first <= SHORT_MAX {
+            return usize::from(first);
+        }
+        let len = usize::from(first - SHORT_MAX);
+        self.bytes[start + 1..start + 1 + len]
+            .iter()
+            .fold(0u64, |index, byte| (index << 8) | u64::from(*byte)) as usize
     }
 }
 
-impl Eq for IndexPath {}
+impl fmt::Debug for IndexPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.indices()).finish()
+    }
+}
 
 impl PartialOrd for IndexPath {
     fn partial_cmp(&self, o: &Self) -> Option<Ordering> {
-        o.indices.partial_cmp(&self.indices)
+        Some(self.cmp(o))
     }
 }
 
+// Reversed, so that `BinaryHeap` pops the first path in depth first order.
 impl Ord for IndexPath {
     fn cmp(&self, o: &Self) -> Ordering {
-        o.indices.cmp(&self.indices)
+        o.bytes.cmp(&self.bytes)
     }
 }
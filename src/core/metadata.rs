@@ -85,6 +85,37 @@ pub struct MetaDataExt {
     pub file_index: Option<u64>,
 }
 
+impl MetaDataExt {
+    /// Identity of the underlying file as `(device, inode)` pair. Two entries
+    /// with the same key are hard links of the same file, or the same
+    /// directory reached over different paths.
+    ///
+    /// On Windows the volume serial number and file index are used. `None` is
+    /// returned if the filesystem doesn't provide them.
+    pub fn inode_key(&self) -> Option<(u64, u64)> {
+        #[cfg(unix)]
+        {
+            Some((self.st_dev, self.st_ino))
+        }
+        #[cfg(windows)]
+        {
+            Some((self.volume_serial_number? as u64, self.file_index?))
+        }
+    }
+
+    /// Number of hard links pointing to the underlying file, if known.
+    pub fn link_count(&self) -> Option<u64> {
+        #[cfg(unix)]
+        {
+            Some(self.st_nlink)
+        }
+        #[cfg(windows)]
+        {
+            self.number_of_links.map(u64::from)
+        }
+    }
+}
+
 #[cfg(windows)]
 pub trait FileExt: std::os::windows::io::AsRawHandle {
     // This code is from the Rust stdlib https://github.com/rust-lang/rust/blob/30ddb5a8c1e85916da0acdc665d6a16535a12dd6/src/libstd/sys/windows/fs.rs#L458-L478
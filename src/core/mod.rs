@@ -1,14 +1,27 @@
+mod checkpoint;
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+mod content_hash;
 mod dir_entry;
 mod dir_entry_iter;
+mod entry_filter;
 mod metadata;
 mod error;
+mod file_name;
+mod fold_dirs_iter;
+mod hidden;
 mod index_path;
 mod ordered;
 mod ordered_queue;
+mod par_iter;
 mod read_dir;
 mod read_dir_iter;
 mod read_dir_spec;
+#[cfg(feature = "regex")]
+mod regex_filter;
+mod retry;
 mod run_context;
+mod tree_iter;
+mod uring;
 
 use rayon::prelude::*;
 use std::sync::atomic::AtomicBool;
@@ -21,11 +34,30 @@ use ordered_queue::*;
 use read_dir_iter::*;
 use run_context::*;
 
+pub(crate) use checkpoint::apply_resume_cursor;
+pub use checkpoint::Checkpoint;
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+pub use content_hash::{ContentHash, HashAlgorithm};
 pub use dir_entry::DirEntry;
+pub(crate) use dir_entry::FollowLinkAncestor;
 pub use dir_entry_iter::DirEntryIter;
+pub(crate) use entry_filter::EntryFilter;
+pub use entry_filter::EntryType;
 pub use error::Error;
+pub use file_name::FileName;
+pub use fold_dirs_iter::FoldDirsIter;
+pub use hidden::Hidden;
+pub(crate) use par_iter::par_iter;
 pub use read_dir::ReadDir;
+pub(crate) use read_dir_iter::ReadDirCallback;
 pub use read_dir_spec::ReadDirSpec;
+#[cfg(feature = "regex")]
+pub(crate) use regex_filter::{relative_dir, RegexFilter};
+#[cfg(feature = "regex")]
+pub use regex_filter::RegexTarget;
+pub use retry::RetryPolicy;
+pub use tree_iter::{TreeIter, TreeOptions};
+pub(crate) use uring::PrefetchedMetadata;
 pub use metadata::{get_metadata_ext, MetaData, MetaDataExt};
 
-use crate::{ClientState, Parallelism};
+use crate::{ClientState, Parallelism, ResultOrdering};
@@ -41,6 +41,7 @@ struct OrderedMatcher {
 pub(crate) fn new_ordered_queue<T>(
     stop: Arc<AtomicBool>,
     ordering: Ordering,
+    root_count: usize,
 ) -> (OrderedQueue<T>, OrderedQueueIter<T>)
 where
     T: Send,
@@ -56,7 +57,7 @@ where
         OrderedQueueIter {
             ordering,
             receiver,
-            ordered_matcher: OrderedMatcher::default(),
+            ordered_matcher: OrderedMatcher::new(root_count),
             receive_buffer: BinaryHeap::new(),
             pending_count,
             stop,
@@ -76,6 +77,10 @@ where
     pub fn complete_item(&self) {
         self.pending_count.fetch_sub(1, AtomicOrdering::SeqCst);
     }
+
+    pub(crate) fn pending_count(&self) -> Arc<AtomicUsize> {
+        self.pending_count.clone()
+    }
 }
 
 impl<T> Clone for OrderedQueue<T>
@@ -103,18 +108,30 @@ where
         self.stop.load(AtomicOrdering::SeqCst)
     }
 
+    /// Finish relaxed iteration once the items counted by `pending_count`
+    /// are completed instead of the items of this queue. Used for a queue of
+    /// results which are pushed before the work producing them is completed.
+    pub(crate) fn complete_with(mut self, pending_count: Arc<AtomicUsize>) -> Self {
+        self.pending_count = pending_count;
+        self
+    }
+
     fn try_next_relaxed(&mut self) -> Result<Ordered<T>, TryRecvError> {
         if self.is_stop() {
             return Err(TryRecvError::Disconnected);
         }
 
+        // Checked before receiving, everything pushed by completed items is
+        // in the channel by then
+        let completed = self.pending_count() == 0;
+
         while let Ok(ordered_work) = self.receiver.try_recv() {
             self.receive_buffer.push(ordered_work)
         }
 
         if let Some(ordered_work) = self.receive_buffer.pop() {
             Ok(ordered_work)
-        } else if self.pending_count() == 0 {
+        } else if completed {
             Err(TryRecvError::Disconnected)
         } else {
             Err(TryRecvError::Empty)
@@ -129,6 +146,15 @@ where
                 return Err(TryRecvError::Disconnected);
             }
 
+            // Results preceding the one looked for belong to skipped subtrees
+            while self
+                .receive_buffer
+                .peek()
+                .is_some_and(|top_ordered| top_ordered.index_path.precedes(looking_for))
+            {
+                self.receive_buffer.pop();
+            }
+
             let top_ordered = self.receive_buffer.peek();
             if let Some(top_ordered) = top_ordered {
                 if top_ordered.index_path.eq(looking_for) {
@@ -155,6 +181,14 @@ where
         self.ordered_matcher.advance_past(&ordered);
         Ok(ordered)
     }
+
+    /// Don't wait for the remaining children of the level currently matched.
+    /// Returns the index path looked for next, all index paths before it
+    /// are not needed anymore.
+    pub(crate) fn skip_remaining_children(&mut self) -> IndexPath {
+        self.ordered_matcher.skip_remaining_children();
+        self.ordered_matcher.looking_for.clone()
+    }
 }
 
 impl<T> Iterator for OrderedQueueIter<T>
@@ -198,23 +232,33 @@ impl OrderedMatcher {
             self.child_count_stack.push(ordered.child_count);
         } else {
             self.looking_for.increment_last();
-            while !self.child_count_stack.is_empty() && *self.child_count_stack.last().unwrap() == 0
-            {
-                self.looking_for.pop();
-                self.child_count_stack.pop();
-                if !self.looking_for.is_empty() {
-                    self.looking_for.increment_last();
-                }
+            self.pop_completed_levels();
+        }
+    }
+
+    fn skip_remaining_children(&mut self) {
+        if let Some(remaining_children) = self.child_count_stack.last_mut() {
+            *remaining_children = 0;
+            self.pop_completed_levels();
+        }
+    }
+
+    fn pop_completed_levels(&mut self) {
+        while !self.child_count_stack.is_empty() && *self.child_count_stack.last().unwrap() == 0 {
+            self.looking_for.pop();
+            self.child_count_stack.pop();
+            if !self.looking_for.is_empty() {
+                self.looking_for.increment_last();
             }
         }
     }
 }
 
-impl Default for OrderedMatcher {
-    fn default() -> OrderedMatcher {
+impl OrderedMatcher {
+    fn new(root_count: usize) -> OrderedMatcher {
         OrderedMatcher {
-            looking_for: IndexPath::new(vec![0]),
-            child_count_stack: vec![1],
+            looking_for: IndexPath::new(&[0]),
+            child_count_stack: vec![root_count],
         }
     }
 }
@@ -0,0 +1,93 @@
+use rayon::iter::{walk_tree_prefix, IntoParallelIterator, ParallelIterator};
+
+use super::dir_entry_iter::{child_entry_id, yielded_count};
+use super::*;
+use crate::Result;
+
+// Node of the tree walked by rayon's `walk_tree_prefix`.
+struct WalkNode<C: ClientState> {
+    dir_entry_result: Result<DirEntry<C>>,
+    // State passed to the read_dir of the entry's children, only set if the
+    // entry has children to read.
+    read_dir_state: Option<C::ReadDirState>,
+}
+
+impl<C: ClientState> WalkNode<C> {
+    // Wrap the results of a directory, setting the sibling position of the
+    // entries like `DirEntryIter` does.
+    fn from_results(
+        parent_id: u64,
+        read_dir_state: &C::ReadDirState,
+        results_list: Vec<Result<DirEntry<C>>>,
+    ) -> Vec<WalkNode<C>> {
+        let sibling_count = yielded_count(&results_list);
+        let mut yielded = 0;
+        results_list
+            .into_iter()
+            .enumerate()
+            .map(|(index, mut dir_entry_result)| {
+                let mut node_read_dir_state = None;
+                if let Ok(dir_entry) = dir_entry_result.as_mut() {
+                    if !dir_entry.skip_yield {
+                        dir_entry.index_in_parent = yielded;
+                        dir_entry.sibling_count = sibling_count;
+                        yielded += 1;
+                    }
+                    dir_entry.entry_id = child_entry_id(parent_id, index);
+                    if dir_entry.read_children_path.is_some() {
+                        node_read_dir_state = Some(read_dir_state.clone());
+                    }
+                }
+                WalkNode {
+                    dir_entry_result,
+                    read_dir_state: node_read_dir_state,
+                }
+            })
+            .collect()
+    }
+
+    // Read the children of this node, runs on a worker thread.
+    fn children(&self, core_read_dir_callback: &ReadDirCallback<C>) -> Vec<WalkNode<C>> {
+        let (Ok(dir_entry), Some(read_dir_state)) =
+            (self.dir_entry_result.as_ref(), self.read_dir_state.as_ref())
+        else {
+            return Vec::new();
+        };
+        let Some(read_dir_spec) = dir_entry.read_children_spec(read_dir_state.clone()) else {
+            return Vec::new();
+        };
+        match core_read_dir_callback(read_dir_spec) {
+            Ok(read_dir) => WalkNode::from_results(
+                dir_entry.entry_id,
+                &read_dir.read_dir_state,
+                read_dir.results_list,
+            ),
+            Err(err) => vec![WalkNode {
+                dir_entry_result: Err(err),
+                read_dir_state: None,
+            }],
+        }
+    }
+}
+
+/// Parallel iterator over all entries of a walk, see
+/// `WalkDirGeneric::into_par_iter`.
+pub(crate) fn par_iter<C: ClientState>(
+    root_entry_results: Vec<Result<DirEntry<C>>>,
+    min_depth: usize,
+    root_read_dir_state: C::ReadDirState,
+    core_read_dir_callback: Arc<ReadDirCallback<C>>,
+) -> impl ParallelIterator<Item = Result<DirEntry<C>>> {
+    WalkNode::from_results(0, &root_read_dir_state, root_entry_results)
+        .into_par_iter()
+        .flat_map(move |root_node| {
+            let core_read_dir_callback = core_read_dir_callback.clone();
+            walk_tree_prefix(root_node, move |node| {
+                node.children(core_read_dir_callback.as_ref())
+            })
+        })
+        .filter_map(move |node| match node.dir_entry_result {
+            Ok(dir_entry) if dir_entry.depth < min_depth || dir_entry.skip_yield => None,
+            dir_entry_result => Some(dir_entry_result),
+        })
+}
@@ -1,3 +1,5 @@
+use std::any::Any;
+
 use super::{ClientState, DirEntry, IndexPath, Ordered, ReadDirSpec};
 use crate::Result;
 
@@ -6,6 +8,10 @@ use crate::Result;
 pub struct ReadDir<C: ClientState> {
     pub(crate) read_dir_state: C::ReadDirState,
     pub(crate) results_list: Vec<Result<DirEntry<C>>>,
+    // Number of retries needed to open the directory.
+    pub(crate) retries: usize,
+    // Partial `fold_dirs` value computed from `results_list`.
+    pub(crate) fold_value: Option<Box<dyn Any + Send>>,
 }
 
 impl<C: ClientState> ReadDir<C> {
@@ -16,6 +22,8 @@ impl<C: ClientState> ReadDir<C> {
         ReadDir {
             read_dir_state,
             results_list,
+            retries: 0,
+            fold_value: None,
         }
     }
 
@@ -1,3 +1,5 @@
+use std::sync::RwLock;
+
 use super::*;
 use crate::Result;
 
@@ -17,6 +19,7 @@ pub enum ReadDirIter<C: ClientState> {
     },
     ParWalk {
         read_dir_result_iter: OrderedQueueIter<Result<ReadDir<C>>>,
+        skipped_before: Arc<RwLock<Option<IndexPath>>>,
     },
 }
 
@@ -24,31 +27,52 @@ impl<C: ClientState> ReadDirIter<C> {
     pub(crate) fn try_new(
         read_dir_specs: Vec<ReadDirSpec<C>>,
         parallelism: Parallelism,
+        ordering: ResultOrdering,
         core_read_dir_callback: Arc<ReadDirCallback<C>>,
     ) -> Option<Self> {
         if let Parallelism::Serial = parallelism {
+            // Specs are popped from the end of the stack, first root last.
+            let mut read_dir_spec_stack = read_dir_specs;
+            read_dir_spec_stack.reverse();
             ReadDirIter::Walk {
-                read_dir_spec_stack: read_dir_specs,
+                read_dir_spec_stack,
                 core_read_dir_callback,
             }
         } else {
+            let root_count = read_dir_specs.len();
             let stop = Arc::new(AtomicBool::new(false));
-            let read_dir_result_queue = new_ordered_queue(stop.clone(), Ordering::Strict);
-            let (read_dir_result_queue, read_dir_result_iter) = read_dir_result_queue;
-            let read_dir_spec_queue = new_ordered_queue(stop.clone(), Ordering::Relaxed);
+            let read_dir_spec_queue =
+                new_ordered_queue(stop.clone(), Ordering::Relaxed, root_count);
             let (read_dir_spec_queue, read_dir_spec_iter) = read_dir_spec_queue;
+            let read_dir_result_queue = match ordering {
+                ResultOrdering::Strict => {
+                    new_ordered_queue(stop.clone(), Ordering::Strict, root_count)
+                }
+                ResultOrdering::Relaxed => {
+                    // Every result is pushed before its spec is completed
+                    let (queue, iter) =
+                        new_ordered_queue(stop.clone(), Ordering::Relaxed, root_count);
+                    (
+                        queue,
+                        iter.complete_with(read_dir_spec_queue.pending_count()),
+                    )
+                }
+            };
+            let (read_dir_result_queue, read_dir_result_iter) = read_dir_result_queue;
 
             for (i, read_dir_spec) in read_dir_specs.into_iter().enumerate() {
                 read_dir_spec_queue
-                    .push(Ordered::new(read_dir_spec, IndexPath::new(vec![0]), i))
+                    .push(Ordered::new(read_dir_spec, IndexPath::new(&[i]), 0))
                     .unwrap();
             }
 
+            let skipped_before = Arc::new(RwLock::new(None));
             let run_context = RunContext {
                 stop,
                 read_dir_spec_queue,
                 read_dir_result_queue,
                 core_read_dir_callback,
+                skipped_before: skipped_before.clone(),
             };
 
             let (startup_tx, startup_rx) = parallelism
@@ -72,15 +96,54 @@ impl<C: ClientState> ReadDirIter<C> {
                     },
                 );
             });
-            if startup_rx.map_or(false, |(rx, duration)| rx.recv_timeout(duration).is_err()) {
+            if startup_rx.is_some_and(|(rx, duration)| rx.recv_timeout(duration).is_err()) {
                 return None;
             }
             ReadDirIter::ParWalk {
                 read_dir_result_iter,
+                skipped_before,
             }
         }
         .into()
     }
+
+    /// Next ReadDir of a parallel walk together with its index path, in the
+    /// order of the result queue. Serial walks use `next` instead.
+    pub(crate) fn next_ordered(&mut self) -> Option<Ordered<Result<ReadDir<C>>>> {
+        match self {
+            ReadDirIter::Walk { .. } => None,
+            ReadDirIter::ParWalk {
+                read_dir_result_iter,
+                ..
+            } => read_dir_result_iter.next(),
+        }
+    }
+
+    /// Skip the next `count` ReadDirs, which are the remaining children of
+    /// the directory read last, together with all their descendants. Pending
+    /// reads of the skipped directories are cancelled.
+    pub(crate) fn skip_read_dirs(&mut self, count: usize) {
+        if count == 0 {
+            return;
+        }
+        match self {
+            ReadDirIter::Walk {
+                read_dir_spec_stack,
+                ..
+            } => {
+                // Descendants of the skipped specs have not been pushed yet
+                let len = read_dir_spec_stack.len().saturating_sub(count);
+                read_dir_spec_stack.truncate(len);
+            }
+            ReadDirIter::ParWalk {
+                read_dir_result_iter,
+                skipped_before,
+            } => {
+                let looking_for = read_dir_result_iter.skip_remaining_children();
+                *skipped_before.write().unwrap() = Some(looking_for);
+            }
+        }
+    }
 }
 
 impl<C: ClientState> Iterator for ReadDirIter<C> {
@@ -108,10 +171,8 @@ impl<C: ClientState> Iterator for ReadDirIter<C> {
                 Some(read_dir_result)
             }
 
-            ReadDirIter::ParWalk {
-                read_dir_result_iter,
-            } => read_dir_result_iter
-                .next()
+            ReadDirIter::ParWalk { .. } => self
+                .next_ordered()
                 .map(|read_dir_result| read_dir_result.value),
         }
     }
@@ -127,6 +188,11 @@ fn multi_threaded_walk_dir<C: ClientState>(
         ..
     } = ordered_read_dir_spec;
 
+    if run_context.is_skipped(&index_path) {
+        run_context.complete_item();
+        return;
+    }
+
     let read_dir_result = (run_context.core_read_dir_callback)(read_dir_spec);
     let ordered_read_children_specs = read_dir_result
         .as_ref()
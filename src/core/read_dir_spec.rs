@@ -1,6 +1,8 @@
+use std::ffi::OsString;
 use std::path::Path;
 use std::sync::Arc;
 
+use crate::core::FollowLinkAncestor;
 use crate::ClientState;
 
 /// Specification for reading a directory.
@@ -23,5 +25,9 @@ pub struct ReadDirSpec<C: ClientState> {
     /// `.gitignore` state to filter entries during the walk.
     pub client_read_state: C::ReadDirState,
     // Origins of symlinks followed to get to this entry.
-    pub(crate) follow_link_ancestors: Arc<Vec<Arc<Path>>>,
+    pub(crate) follow_link_ancestors: Arc<Vec<Arc<FollowLinkAncestor>>>,
+    // Identity of the directory if known from the metadata of its entry.
+    pub(crate) inode_key: Option<(u64, u64)>,
+    // Remaining file names of a resume cursor below this directory.
+    pub(crate) resume_cursor: Option<Arc<[OsString]>>,
 }
@@ -0,0 +1,67 @@
+use regex::bytes::RegexSet;
+use std::ffi::OsStr;
+use std::path::Path;
+
+use super::dir_entry::relative_components;
+
+/// Part of an entry matched by
+/// [`WalkDirGeneric::match_regex`](struct.WalkDirGeneric.html#method.match_regex)
+/// and [`WalkDirGeneric::exclude_regex`](struct.WalkDirGeneric.html#method.exclude_regex).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RegexTarget {
+    /// The file name of the entry.
+    FileName,
+    /// The path of the entry relative to its root, with `/` as separator on
+    /// every platform, e.g. `src/core/mod.rs`.
+    RelativePath,
+}
+
+// Compiled patterns of match_regex or exclude_regex.
+#[derive(Clone, Debug)]
+pub(crate) struct RegexFilter {
+    regex_set: RegexSet,
+    target: RegexTarget,
+}
+
+impl RegexFilter {
+    pub(crate) fn new(regex_set: RegexSet, target: RegexTarget) -> RegexFilter {
+        RegexFilter { regex_set, target }
+    }
+
+    pub(crate) fn needs_relative_path(&self) -> bool {
+        self.target == RegexTarget::RelativePath
+    }
+
+    // Whether any pattern matches the entry `file_name` in the directory with
+    // the root relative path `relative_dir`.
+    pub(crate) fn is_match(&self, relative_dir: &[u8], file_name: &OsStr) -> bool {
+        let file_name = file_name.as_encoded_bytes();
+        match self.target {
+            RegexTarget::FileName => self.regex_set.is_match(file_name),
+            RegexTarget::RelativePath if relative_dir.is_empty() => {
+                self.regex_set.is_match(file_name)
+            }
+            RegexTarget::RelativePath => {
+                let mut relative_path =
+                    Vec::with_capacity(relative_dir.len() + 1 + file_name.len());
+                relative_path.extend_from_slice(relative_dir);
+                relative_path.push(b'/');
+                relative_path.extend_from_slice(file_name);
+                self.regex_set.is_match(&relative_path)
+            }
+        }
+    }
+}
+
+// Path of the directory `path` at `depth` relative to its root: its last
+// `depth` components joined by `/`.
+pub(crate) fn relative_dir(path: &Path, depth: usize) -> Vec<u8> {
+    let mut relative_dir = Vec::new();
+    for name in relative_components(path, depth) {
+        if !relative_dir.is_empty() {
+            relative_dir.push(b'/');
+        }
+        relative_dir.extend_from_slice(name.as_encoded_bytes());
+    }
+    relative_dir
+}
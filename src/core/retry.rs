@@ -0,0 +1,87 @@
+use std::io;
+use std::thread;
+use std::time::Duration;
+
+/// Policy for retrying transient I/O errors.
+///
+/// Network and FUSE filesystems occasionally fail `read_dir` or `stat` calls
+/// with errors that go away when the call is repeated. Without retrying, such
+/// an error hides the entire subtree below the failing directory.
+///
+/// The policy is applied when opening a directory with `fs::read_dir` and when
+/// reading the metadata of an entry. The number of retries that were needed is
+/// reported in [`DirEntry::retries`](struct.DirEntry.html#structfield.retries).
+///
+/// The default policy performs a single attempt, i.e. it never retries.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one. Values below `2`
+    /// disable retrying.
+    pub max_attempts: usize,
+    /// Delay before the first retry. The delay doubles after every further
+    /// failed attempt.
+    pub backoff: Duration,
+    /// Error kinds that are considered transient.
+    pub kinds: Vec<io::ErrorKind>,
+    /// Raw OS error codes that are considered transient. Useful for errors
+    /// such as `EIO` which have no dedicated [`io::ErrorKind`].
+    pub raw_os_errors: Vec<i32>,
+}
+
+impl RetryPolicy {
+    /// Policy retrying the errors typically seen on NFS and FUSE mounts:
+    /// `EINTR`, `EAGAIN`, `ESTALE` and `EIO`.
+    pub fn transient(max_attempts: usize, backoff: Duration) -> RetryPolicy {
+        RetryPolicy {
+            max_attempts,
+            backoff,
+            kinds: vec![io::ErrorKind::Interrupted, io::ErrorKind::WouldBlock],
+            #[cfg(unix)]
+            raw_os_errors: vec![libc::ESTALE, libc::EIO],
+            #[cfg(not(unix))]
+            raw_os_errors: Vec::new(),
+        }
+    }
+
+    /// Returns `true` if `err` should be retried according to this policy.
+    pub fn is_transient(&self, err: &io::Error) -> bool {
+        self.kinds.contains(&err.kind())
+            || err
+                .raw_os_error()
+                .is_some_and(|code| self.raw_os_errors.contains(&code))
+    }
+
+    /// Run `op` until it succeeds, fails with a non transient error or the
+    /// maximum number of attempts is reached. Returns the last result together
+    /// with the number of retries performed.
+    pub(crate) fn run<T, F>(&self, mut op: F) -> (io::Result<T>, usize)
+    where
+        F: FnMut() -> io::Result<T>,
+    {
+        let mut backoff = self.backoff;
+        let mut retries = 0;
+        loop {
+            match op() {
+                Err(err) if retries + 1 < self.max_attempts && self.is_transient(&err) => {
+                    if !backoff.is_zero() {
+                        thread::sleep(backoff);
+                        backoff = backoff.saturating_mul(2);
+                    }
+                    retries += 1;
+                }
+                result => return (result, retries),
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            backoff: Duration::ZERO,
+            kinds: Vec::new(),
+            raw_os_errors: Vec::new(),
+        }
+    }
+}
@@ -1,7 +1,7 @@
 use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 
-use super::{ClientState, Ordered, OrderedQueue, ReadDir, ReadDirCallback, ReadDirSpec};
+use super::{ClientState, IndexPath, Ordered, OrderedQueue, ReadDir, ReadDirCallback, ReadDirSpec};
 use crate::Result;
 
 pub(crate) struct RunContext<C: ClientState> {
@@ -9,6 +9,8 @@ pub(crate) struct RunContext<C: ClientState> {
     pub(crate) read_dir_spec_queue: OrderedQueue<ReadDirSpec<C>>,
     pub(crate) read_dir_result_queue: OrderedQueue<Result<ReadDir<C>>>,
     pub(crate) core_read_dir_callback: Arc<ReadDirCallback<C>>,
+    // Specs before this index path belong to subtrees skipped by the consumer
+    pub(crate) skipped_before: Arc<RwLock<Option<IndexPath>>>,
 }
 
 impl<C: ClientState> RunContext<C> {
@@ -30,6 +32,15 @@ impl<C: ClientState> RunContext<C> {
     pub(crate) fn complete_item(&self) {
         self.read_dir_spec_queue.complete_item()
     }
+
+    pub(crate) fn is_skipped(&self, index_path: &IndexPath) -> bool {
+        match &*self.skipped_before.read().unwrap() {
+            Some(skipped_before) => {
+                skipped_before.is_empty() || index_path.precedes(skipped_before)
+            }
+            None => false,
+        }
+    }
 }
 
 impl<C: ClientState> Clone for RunContext<C> {
@@ -39,6 +50,7 @@ impl<C: ClientState> Clone for RunContext<C> {
             read_dir_spec_queue: self.read_dir_spec_queue.clone(),
             read_dir_result_queue: self.read_dir_result_queue.clone(),
             core_read_dir_callback: self.core_read_dir_callback.clone(),
+            skipped_before: self.skipped_before.clone(),
         }
     }
 }
@@ -0,0 +1,260 @@
+use std::collections::VecDeque;
+use std::fmt::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::*;
+use crate::Result;
+
+/// Options for rendering a walk with
+/// [`WalkDirGeneric::tree`](struct.WalkDirGeneric.html#method.tree).
+#[derive(Clone, Debug)]
+pub struct TreeOptions {
+    /// Show the size of files in bytes.
+    pub sizes: bool,
+    /// Show the modification time of files and directories (UTC).
+    pub dates: bool,
+    /// Show the total size and number of files of every directory. Totals
+    /// are only known once a directory is complete, so they are rendered
+    /// below the last child of the directory. For directories at
+    /// `max_depth` the total is appended to the directory line instead.
+    pub dir_totals: bool,
+    /// Deepest level that is rendered. Deeper entries are still walked if
+    /// `dir_totals` needs them.
+    pub max_depth: usize,
+    /// End with a line counting the rendered directories and files.
+    pub report: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> TreeOptions {
+        TreeOptions {
+            sizes: false,
+            dates: false,
+            dir_totals: false,
+            max_depth: usize::MAX,
+            report: true,
+        }
+    }
+}
+
+/// Iterator returned by
+/// [`WalkDirGeneric::tree`](struct.WalkDirGeneric.html#method.tree).
+///
+/// Yields the lines of an indented tree drawn with box-drawing characters,
+/// without line terminators. Errors of the walk are yielded in between.
+pub struct TreeIter<C: ClientState> {
+    dir_entry_iter: DirEntryIter<C>,
+    options: TreeOptions,
+    // For every ancestor level below the root, true if the ancestor is the
+    // last entry of its parent. Determines the `│` columns of the prefix.
+    last_flags: Vec<bool>,
+    // Directories whose totals are still being summed up, deepest last
+    totals: Vec<DirTotal>,
+    ready: VecDeque<Result<String>>,
+    dirs: usize,
+    files: usize,
+    done: bool,
+}
+
+struct DirTotal {
+    depth: usize,
+    // Prefix of the lines of the children
+    prefix: String,
+    // Line of a directory at `max_depth`, held back until its total is known
+    held_line: Option<String>,
+    size: u64,
+    files: usize,
+}
+
+impl<C: ClientState> TreeIter<C> {
+    pub(crate) fn new(dir_entry_iter: DirEntryIter<C>, options: TreeOptions) -> TreeIter<C> {
+        TreeIter {
+            dir_entry_iter,
+            options,
+            last_flags: Vec::new(),
+            totals: Vec::new(),
+            ready: VecDeque::new(),
+            dirs: 0,
+            files: 0,
+            done: false,
+        }
+    }
+
+    // Complete all directories that can't receive further descendants once an
+    // entry at `depth` is seen.
+    fn complete_dirs(&mut self, depth: usize) {
+        while self.totals.last().is_some_and(|total| total.depth >= depth) {
+            let total = self.totals.pop().unwrap();
+            if let Some(parent) = self.totals.last_mut() {
+                parent.size += total.size;
+                parent.files += total.files;
+            }
+            let summary = format_total(total.size, total.files);
+            let line = match total.held_line {
+                Some(line) => format!("{}  {}", line, summary),
+                None => format!("{}{}", total.prefix, summary),
+            };
+            self.ready.push_back(Ok(line));
+        }
+    }
+
+    fn render(&mut self, dir_entry: &DirEntry<C>) {
+        let depth = dir_entry.depth;
+        let is_dir = dir_entry.file_type.is_dir();
+        let size = dir_entry
+            .metadata
+            .as_ref()
+            .map_or(0, |metadata| metadata.size);
+
+        if self.options.dir_totals {
+            self.complete_dirs(depth);
+            if !is_dir {
+                if let Some(parent) = self.totals.last_mut() {
+                    parent.size += size;
+                    parent.files += 1;
+                }
+            }
+        }
+        if depth > self.options.max_depth {
+            return;
+        }
+
+        let mut line = String::new();
+        if depth > 0 {
+            self.last_flags.truncate(depth - 1);
+            for last in &self.last_flags {
+                line.push_str(if *last { "    " } else { "│   " });
+            }
+            let is_last = dir_entry.is_last_sibling();
+            line.push_str(if is_last { "└── " } else { "├── " });
+            self.last_flags.push(is_last);
+            if is_dir {
+                self.dirs += 1;
+            } else {
+                self.files += 1;
+            }
+        } else {
+            self.last_flags.clear();
+        }
+
+        let show_size = self.options.sizes && !is_dir;
+        if show_size || self.options.dates {
+            line.push('[');
+            if show_size {
+                let _ = write!(line, "{:>10}", size);
+            }
+            if self.options.dates {
+                if show_size {
+                    line.push_str("  ");
+                }
+                let modified = dir_entry.metadata.as_ref().and_then(|m| m.modified);
+                line.push_str(&modified.map_or_else(|| " ".repeat(16), format_date));
+            }
+            line.push_str("]  ");
+        }
+
+        if depth == 0 {
+            line.push_str(&dir_entry.path().to_string_lossy());
+        } else {
+            line.push_str(&dir_entry.file_name.to_string_lossy());
+        }
+
+        if self.options.dir_totals && is_dir && dir_entry.read_children_path.is_some() {
+            let mut prefix = String::new();
+            if depth > 0 {
+                for last in &self.last_flags {
+                    prefix.push_str(if *last { "    " } else { "│   " });
+                }
+            }
+            let held_line = if depth == self.options.max_depth {
+                Some(line)
+            } else {
+                self.ready.push_back(Ok(line));
+                None
+            };
+            self.totals.push(DirTotal {
+                depth,
+                prefix,
+                held_line,
+                size: 0,
+                files: 0,
+            });
+        } else {
+            self.ready.push_back(Ok(line));
+        }
+    }
+}
+
+impl<C: ClientState> Iterator for TreeIter<C> {
+    type Item = Result<String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(ready) = self.ready.pop_front() {
+                return Some(ready);
+            }
+            if self.done {
+                return None;
+            }
+            match self.dir_entry_iter.next() {
+                Some(Ok(dir_entry)) => self.render(&dir_entry),
+                Some(Err(err)) => self.ready.push_back(Err(err)),
+                None => {
+                    self.complete_dirs(0);
+                    if self.options.report {
+                        self.ready.push_back(Ok(String::new()));
+                        self.ready.push_back(Ok(format!(
+                            "{} {}, {} {}",
+                            self.dirs,
+                            if self.dirs == 1 {
+                                "directory"
+                            } else {
+                                "directories"
+                            },
+                            self.files,
+                            if self.files == 1 { "file" } else { "files" },
+                        )));
+                    }
+                    self.done = true;
+                }
+            }
+        }
+    }
+}
+
+fn format_total(size: u64, files: usize) -> String {
+    format!(
+        "({} {}, {} bytes)",
+        files,
+        if files == 1 { "file" } else { "files" },
+        size
+    )
+}
+
+// Format as `YYYY-MM-DD HH:MM` in UTC.
+fn format_date(time: SystemTime) -> String {
+    let secs = match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(err) => -(err.duration().as_secs() as i64),
+    };
+    let days = secs.div_euclid(86400);
+    let secs_of_day = secs.rem_euclid(86400);
+    // Civil date from days since 1970-01-01, see
+    // http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}",
+        year,
+        month,
+        day,
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60
+    )
+}
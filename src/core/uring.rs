@@ -0,0 +1,228 @@
+//! Batched metadata of directory entries through io_uring `statx` on Linux,
+//! enabled with the `io-uring` feature.
+
+use std::fs;
+use std::path::Path;
+
+use super::*;
+
+/// Metadata of the entries of one directory, read in a batch before the
+/// entries are constructed. Entries without prefetched metadata fall back to
+/// the regular `metadata()` calls.
+pub(crate) struct PrefetchedMetadata {
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    results: Vec<Option<libc::statx>>,
+}
+
+impl PrefetchedMetadata {
+    /// Prefetch the metadata of the `Ok` entries of `fs_dir_entries` read
+    /// from `dir`. Returns `None` if io_uring is not enabled or not available.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn read(
+        dir: &Path,
+        fs_dir_entries: &[std::io::Result<fs::DirEntry>],
+    ) -> Option<PrefetchedMetadata> {
+        linux::statx_batch(dir, fs_dir_entries).map(|results| PrefetchedMetadata { results })
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+    pub(crate) fn read(
+        _dir: &Path,
+        _fs_dir_entries: &[std::io::Result<fs::DirEntry>],
+    ) -> Option<PrefetchedMetadata> {
+        None
+    }
+
+    /// Metadata of the entry at `index`, not following symlinks, like
+    /// `fs::DirEntry::metadata`. The extended metadata is only returned for
+    /// entries which are not symlinks, it follows symlinks otherwise.
+    #[cfg(all(target_os = "linux", feature = "io-uring"))]
+    pub(crate) fn get(&self, index: usize) -> Option<(MetaData, Option<MetaDataExt>)> {
+        let statx = self.results.get(index)?.as_ref()?;
+        let metadata = linux::metadata_from_statx(statx);
+        let metadata_ext = if metadata.is_symlink {
+            None
+        } else {
+            Some(linux::metadata_ext_from_statx(statx))
+        };
+        Some((metadata, metadata_ext))
+    }
+
+    #[cfg(not(all(target_os = "linux", feature = "io-uring")))]
+    pub(crate) fn get(&self, _index: usize) -> Option<(MetaData, Option<MetaDataExt>)> {
+        None
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "io-uring"))]
+mod linux {
+    use std::cell::RefCell;
+    use std::ffi::CString;
+    use std::fs::{self, File, Permissions};
+    use std::io;
+    use std::mem;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::fs::PermissionsExt;
+    use std::os::unix::io::AsRawFd;
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use io_uring::{opcode, types, IoUring, Probe};
+
+    use super::super::{MetaData, MetaDataExt};
+
+    const RING_ENTRIES: u32 = 256;
+
+    enum RingState {
+        Uninit,
+        Unavailable,
+        Ready(Box<IoUring>),
+    }
+
+    thread_local! {
+        // One ring per worker thread, created on first use.
+        static RING: RefCell<RingState> = const { RefCell::new(RingState::Uninit) };
+    }
+
+    fn new_ring() -> Option<IoUring> {
+        let ring = IoUring::new(RING_ENTRIES).ok()?;
+        let mut probe = Probe::new();
+        ring.submitter().register_probe(&mut probe).ok()?;
+        if probe.is_supported(opcode::Statx::CODE) {
+            Some(ring)
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn statx_batch(
+        dir: &Path,
+        fs_dir_entries: &[io::Result<fs::DirEntry>],
+    ) -> Option<Vec<Option<libc::statx>>> {
+        RING.with(|ring_state| {
+            let mut ring_state = ring_state.borrow_mut();
+            if let RingState::Uninit = *ring_state {
+                *ring_state = match new_ring() {
+                    Some(ring) => RingState::Ready(Box::new(ring)),
+                    None => RingState::Unavailable,
+                };
+            }
+            let RingState::Ready(ring) = &mut *ring_state else {
+                return None;
+            };
+            let results = submit_statx(ring, dir, fs_dir_entries);
+            if results.is_none() {
+                // Don't use a ring which failed to submit again
+                *ring_state = RingState::Unavailable;
+            }
+            results
+        })
+    }
+
+    fn submit_statx(
+        ring: &mut IoUring,
+        dir: &Path,
+        fs_dir_entries: &[io::Result<fs::DirEntry>],
+    ) -> Option<Vec<Option<libc::statx>>> {
+        let dir_file = File::open(dir).ok()?;
+        let dirfd = types::Fd(dir_file.as_raw_fd());
+        let names: Vec<Option<CString>> = fs_dir_entries
+            .iter()
+            .map(|fs_dir_entry| {
+                let file_name = fs_dir_entry.as_ref().ok()?.file_name();
+                CString::new(file_name.as_bytes()).ok()
+            })
+            .collect();
+        // Safety: an all-zero statx is a valid value
+        let mut buffers: Vec<libc::statx> = vec![unsafe { mem::zeroed() }; names.len()];
+        let mut results: Vec<Option<libc::statx>> = vec![None; names.len()];
+
+        let pending: Vec<usize> = (0..names.len())
+            .filter(|index| names[*index].is_some())
+            .collect();
+        for chunk in pending.chunks(RING_ENTRIES as usize) {
+            for index in chunk {
+                let name = names[*index].as_ref().unwrap();
+                let entry = opcode::Statx::new(
+                    dirfd,
+                    name.as_ptr(),
+                    &mut buffers[*index] as *mut libc::statx as *mut types::statx,
+                )
+                .flags(libc::AT_SYMLINK_NOFOLLOW | libc::AT_STATX_SYNC_AS_STAT)
+                .mask(libc::STATX_BASIC_STATS | libc::STATX_BTIME)
+                .build()
+                .user_data(*index as u64);
+                // Safety: the name and buffer outlive the operation, all
+                // completions are awaited below.
+                if unsafe { ring.submission().push(&entry) }.is_err() {
+                    mem::forget(buffers);
+                    mem::forget(names);
+                    return None;
+                }
+            }
+
+            let mut completed = 0;
+            while completed < chunk.len() {
+                match ring.submit_and_wait(chunk.len() - completed) {
+                    Ok(_) => {}
+                    Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                    Err(_) => {
+                        // Operations may still be in flight, keep their memory
+                        mem::forget(buffers);
+                        mem::forget(names);
+                        return None;
+                    }
+                }
+                for completion in ring.completion() {
+                    completed += 1;
+                    if completion.result() == 0 {
+                        let index = completion.user_data() as usize;
+                        results[index] = Some(buffers[index]);
+                    }
+                }
+            }
+        }
+        Some(results)
+    }
+
+    fn system_time(timestamp: &libc::statx_timestamp) -> Option<SystemTime> {
+        let duration = Duration::new(timestamp.tv_sec.unsigned_abs(), timestamp.tv_nsec);
+        if timestamp.tv_sec >= 0 {
+            UNIX_EPOCH.checked_add(duration)
+        } else {
+            UNIX_EPOCH.checked_sub(duration)
+        }
+    }
+
+    pub(super) fn metadata_from_statx(statx: &libc::statx) -> MetaData {
+        let file_type = u32::from(statx.stx_mode) & libc::S_IFMT;
+        MetaData {
+            is_dir: file_type == libc::S_IFDIR,
+            is_file: file_type == libc::S_IFREG,
+            is_symlink: file_type == libc::S_IFLNK,
+            size: statx.stx_size,
+            created: if statx.stx_mask & libc::STATX_BTIME != 0 {
+                system_time(&statx.stx_btime)
+            } else {
+                None
+            },
+            modified: system_time(&statx.stx_mtime),
+            accessed: system_time(&statx.stx_atime),
+            permissions: Some(Permissions::from_mode(u32::from(statx.stx_mode))),
+        }
+    }
+
+    pub(super) fn metadata_ext_from_statx(statx: &libc::statx) -> MetaDataExt {
+        MetaDataExt {
+            st_mode: u32::from(statx.stx_mode),
+            st_ino: statx.stx_ino,
+            st_dev: libc::makedev(statx.stx_dev_major, statx.stx_dev_minor),
+            st_nlink: u64::from(statx.stx_nlink),
+            st_blksize: u64::from(statx.stx_blksize),
+            st_blocks: statx.stx_blocks,
+            st_uid: statx.stx_uid,
+            st_gid: statx.stx_gid,
+            st_rdev: libc::makedev(statx.stx_rdev_major, statx.stx_rdev_minor),
+        }
+    }
+}
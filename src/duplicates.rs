@@ -0,0 +1,220 @@
+//! Find files with identical contents.
+//!
+//! Files are grouped by their size first, which only needs the metadata read
+//! during the walk. Groups with more than one file are narrowed down by a
+//! hash of the first and last KiB of the files and finally by a hash of their
+//! complete contents. Hashing runs on the rayon pool of the walk.
+//!
+//! Hard links to the same file are not reported as duplicates of each other,
+//! only the first path found for an inode takes part.
+//!
+//! ```no_run
+//! # #[cfg(feature = "blake3")]
+//! # fn main() {
+//! use jwalk_meta::duplicates::{find_duplicates, DuplicateOptions};
+//! use jwalk_meta::{HashAlgorithm, WalkDir};
+//!
+//! let options = DuplicateOptions::new(HashAlgorithm::Blake3);
+//! let duplicates = find_duplicates(WalkDir::new_multi(["foo", "bar"]), &options);
+//! for set in duplicates.sets {
+//!     println!("{} bytes: {:?}", set.size, set.paths);
+//! }
+//! # }
+//! # #[cfg(not(feature = "blake3"))]
+//! # fn main() {}
+//! ```
+
+use rayon::prelude::*;
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use crate::{ClientState, ContentHash, Error, HashAlgorithm, Parallelism, WalkDirGeneric};
+
+/// Options of [`find_duplicates`](fn.find_duplicates.html).
+#[derive(Clone, Debug)]
+pub struct DuplicateOptions {
+    /// Digest algorithm used for the partial and the full hash.
+    pub algorithm: HashAlgorithm,
+    /// Smaller files are ignored. Defaults to 1, ignoring empty files.
+    pub min_size: u64,
+    /// Number of KiB from the start and from the end of files hashed to
+    /// narrow down groups of files with equal size. 0 skips this step.
+    /// Defaults to 64.
+    pub partial_kib: u64,
+}
+
+impl DuplicateOptions {
+    /// Default options using `algorithm`.
+    pub fn new(algorithm: HashAlgorithm) -> DuplicateOptions {
+        DuplicateOptions {
+            algorithm,
+            min_size: 1,
+            partial_kib: 64,
+        }
+    }
+}
+
+/// Files with identical contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DuplicateSet {
+    /// Size of each file in bytes.
+    pub size: u64,
+    /// Digest of the contents of the files.
+    pub hash: Vec<u8>,
+    /// Paths of the files, sorted.
+    pub paths: Vec<PathBuf>,
+}
+
+/// Result of [`find_duplicates`](fn.find_duplicates.html).
+#[derive(Debug)]
+pub struct Duplicates {
+    /// Sets of duplicate files, largest files first.
+    pub sets: Vec<DuplicateSet>,
+    /// Errors of the walk and of files which couldn't be read.
+    pub errors: Vec<Error>,
+}
+
+// Files of one size, with the inodes seen
+#[derive(Default)]
+struct SizeGroup {
+    paths: Vec<PathBuf>,
+    inodes: HashSet<(u64, u64)>,
+}
+
+// Group of files which are possibly identical
+struct Candidates {
+    size: u64,
+    paths: Vec<PathBuf>,
+}
+
+/// Walk the roots of `walk_dir` and find the regular files with identical
+/// contents.
+///
+/// All other settings of `walk_dir`, like
+/// [`follow_links`](../struct.WalkDirGeneric.html#method.follow_links) or
+/// [`skip_hidden`](../struct.WalkDirGeneric.html#method.skip_hidden), are
+/// respected. Metadata is read automatically.
+pub fn find_duplicates<C: ClientState>(
+    walk_dir: WalkDirGeneric<C>,
+    options: &DuplicateOptions,
+) -> Duplicates {
+    // The walk and both hash passes run on the same pool
+    let parallelism = walk_dir.options.parallelism.clone().into_existing_pool();
+    let mut errors = Vec::new();
+
+    // 1. Group by size, keeping one path per inode
+    let mut by_size: HashMap<u64, SizeGroup> = HashMap::new();
+    for result in walk_dir
+        .parallelism(parallelism.clone())
+        .read_metadata(true)
+        .read_metadata_ext(true)
+    {
+        let dir_entry = match result {
+            Ok(dir_entry) => dir_entry,
+            Err(err) => {
+                errors.push(err);
+                continue;
+            }
+        };
+        if !dir_entry.file_type.is_file() {
+            continue;
+        }
+        let Some(size) = dir_entry.metadata.as_ref().map(|metadata| metadata.size) else {
+            continue;
+        };
+        if size < options.min_size {
+            continue;
+        }
+        let group = by_size.entry(size).or_default();
+        let inode_key = dir_entry
+            .metadata_ext
+            .as_ref()
+            .and_then(|metadata_ext| metadata_ext.inode_key());
+        if inode_key.is_none_or(|inode_key| group.inodes.insert(inode_key)) {
+            group.paths.push(dir_entry.full_path());
+        }
+    }
+    let mut groups: Vec<Candidates> = by_size
+        .into_iter()
+        .filter(|(_, group)| group.paths.len() > 1)
+        .map(|(size, group)| Candidates {
+            size,
+            paths: group.paths,
+        })
+        .collect();
+
+    // 2. Narrow down by the partial hash. For small files it covers the
+    // complete contents already.
+    let mut sets = Vec::new();
+    let partial = options.partial_kib.saturating_mul(1024);
+    if partial > 0 {
+        let content_hash = ContentHash {
+            partial_kib: Some(options.partial_kib),
+            ..ContentHash::new(options.algorithm)
+        };
+        let partial_sets;
+        (sets, partial_sets) = hash_groups(&parallelism, &content_hash, groups, &mut errors)
+            .into_iter()
+            .partition(|set| set.size <= partial.saturating_mul(2));
+        groups = partial_sets
+            .into_iter()
+            .map(|set| Candidates {
+                size: set.size,
+                paths: set.paths,
+            })
+            .collect();
+    }
+
+    // 3. Compare the hash of the complete contents
+    let content_hash = ContentHash::new(options.algorithm);
+    sets.extend(hash_groups(
+        &parallelism,
+        &content_hash,
+        groups,
+        &mut errors,
+    ));
+
+    for set in &mut sets {
+        set.paths.sort();
+    }
+    sets.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.paths.cmp(&b.paths)));
+    Duplicates { sets, errors }
+}
+
+// Hash every candidate and split the groups by hash, dropping single files.
+fn hash_groups(
+    parallelism: &Parallelism,
+    content_hash: &ContentHash,
+    groups: Vec<Candidates>,
+    errors: &mut Vec<Error>,
+) -> Vec<DuplicateSet> {
+    let candidates: Vec<(u64, PathBuf)> = groups
+        .into_iter()
+        .flat_map(|group| {
+            let size = group.size;
+            group.paths.into_iter().map(move |path| (size, path))
+        })
+        .collect();
+    let hash = |(size, path): (u64, PathBuf)| {
+        let hash = content_hash.hash_file(&path, Some(size));
+        (size, path, hash)
+    };
+    let hashed: Vec<_> = match parallelism {
+        Parallelism::Serial => candidates.into_iter().map(hash).collect(),
+        _ => parallelism.install(|| candidates.into_par_iter().map(hash).collect()),
+    };
+
+    let mut by_hash: HashMap<(u64, Vec<u8>), Vec<PathBuf>> = HashMap::new();
+    for (size, path, hash) in hashed {
+        match hash {
+            Ok(Some(hash)) => by_hash.entry((size, hash)).or_default().push(path),
+            Ok(None) => {}
+            Err(err) => errors.push(Error::from_path(0, path, err)),
+        }
+    }
+    by_hash
+        .into_iter()
+        .filter(|(_, paths)| paths.len() > 1)
+        .map(|((size, hash), paths)| DuplicateSet { size, hash, paths })
+        .collect()
+}
@@ -114,19 +114,43 @@
 //! depth first order.
 
 mod core;
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+pub mod duplicates;
 
+use rayon::iter::{
+    IndexedParallelIterator, IntoParallelIterator, IntoParallelRefMutIterator, ParallelIterator,
+};
+use rayon::slice::ParallelSliceMut;
 use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::any::Any;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fmt::Debug;
-use std::fs;
+use std::fs::{self, FileType};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-use crate::core::{get_metadata_ext, ReadDir, ReadDirSpec};
+use crate::core::{
+    apply_resume_cursor, get_metadata_ext, par_iter, EntryFilter, FollowLinkAncestor,
+    PrefetchedMetadata, ReadDir, ReadDirCallback, ReadDirSpec,
+};
 
-pub use crate::core::{DirEntry, DirEntryIter, Error, MetaData, MetaDataExt};
+#[cfg(feature = "regex")]
+pub use crate::core::RegexTarget;
+#[cfg(feature = "regex")]
+use crate::core::{relative_dir, RegexFilter};
+pub use crate::core::{
+    Checkpoint, DirEntry, DirEntryIter, EntryType, Error, FileName, FoldDirsIter, Hidden, MetaData,
+    MetaDataExt, RetryPolicy, TreeIter, TreeOptions,
+};
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+pub use crate::core::{ContentHash, HashAlgorithm};
 pub use rayon;
+#[cfg(feature = "regex")]
+pub use regex;
 
 /// Builder for walking a directory.
 pub type WalkDir = WalkDirGeneric<((), ())>;
@@ -157,7 +181,7 @@ pub trait ClientState: Send + Default + Debug + 'static {
 /// Use [`WalkDir`](type.WalkDir.html) if you don't need to store client state
 /// into yeilded DirEntries.
 pub struct WalkDirGeneric<C: ClientState> {
-    root: PathBuf,
+    roots: Vec<PathBuf>,
     options: WalkDirOptions<C>,
 }
 
@@ -166,11 +190,23 @@ type ProcessReadDirFunction<C> = dyn Fn(Option<usize>, &Path, &mut <C as ClientS
     + Sync
     + 'static;
 
+// Minimum number of entries of a large directory processed as one task.
+const LARGE_DIR_CHUNK_SIZE: usize = 1024;
+
+type FilterNameFunction = dyn Fn(&OsStr, FileType, usize) -> bool + Send + Sync + 'static;
+
+type ProcessEntryFunction<C> = dyn Fn(&mut DirEntry<C>) + Send + Sync + 'static;
+
+type FoldEntriesFunction<C> =
+    dyn Fn(&[Result<DirEntry<C>>]) -> Box<dyn Any + Send> + Send + Sync + 'static;
+
 /// Degree of parallelism to use when performing walk.
 ///
 /// Parallelism happens at the directory level. It will help when walking deep
-/// filesystems with many directories. It wont help when reading a single
-/// directory with many files.
+/// filesystems with many directories. Reading a single directory is serial,
+/// but the entries of directories larger than
+/// [`large_dir_threshold`](struct.WalkDirGeneric.html#method.large_dir_threshold)
+/// are processed in parallel.
 ///
 /// If you plan to perform lots of per file processing you might want to use Rayon to
 #[derive(Clone)]
@@ -198,17 +234,64 @@ pub enum Parallelism {
     RayonNewPool(usize),
 }
 
+/// How the root is presented in the paths of entries, see
+/// [`root_style`](struct.WalkDirGeneric.html#method.root_style).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RootStyle {
+    /// Paths start with the root as passed to the builder.
+    #[default]
+    AsGiven,
+    /// The root is canonicalized before the walk, paths are absolute and
+    /// free of symbolic links above the root.
+    Canonical,
+    /// [`DirEntry::path`](struct.DirEntry.html#method.path) returns the path
+    /// relative to the root, empty for the root itself. `parent_path` keeps
+    /// the root as given so the file can still be accessed.
+    Stripped,
+}
+
+/// Order in which the results of a parallel walk are yielded, see
+/// [`ordering`](struct.WalkDirGeneric.html#method.ordering).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ResultOrdering {
+    /// Depth first order, the same as a serial walk. The results of a
+    /// directory are held back until all directories before it are yielded.
+    #[default]
+    Strict,
+    /// The entries of each directory are yielded as soon as the directory is
+    /// read, in its own (sorted) order. Parents are yielded before their
+    /// children, but one slow directory doesn't hold back the rest.
+    Relaxed,
+}
+
 struct WalkDirOptions<C: ClientState> {
     sort: bool,
     min_depth: usize,
     max_depth: usize,
-    skip_hidden: bool,
+    hidden: Hidden,
+    filter_name: Option<Arc<FilterNameFunction>>,
+    entry_filter: EntryFilter,
+    #[cfg(feature = "regex")]
+    match_regex: Option<RegexFilter>,
+    #[cfg(feature = "regex")]
+    exclude_regex: Option<RegexFilter>,
     follow_links: bool,
     read_metadata: bool,
     read_metadata_ext: bool,
+    dedupe_roots: bool,
+    dedupe_inodes: bool,
+    root_style: RootStyle,
     parallelism: Parallelism,
+    ordering: ResultOrdering,
+    retry_policy: RetryPolicy,
+    resume_from: Option<Checkpoint>,
     root_read_dir_state: C::ReadDirState,
     process_read_dir: Option<Arc<ProcessReadDirFunction<C>>>,
+    process_entry: Option<Arc<ProcessEntryFunction<C>>>,
+    content_hash: Option<Arc<ProcessEntryFunction<C>>>,
+    process_entry_chunk_size: usize,
+    large_dir_threshold: usize,
+    fold_entries: Option<Arc<FoldEntriesFunction<C>>>,
 }
 
 impl<C: ClientState> WalkDirGeneric<C> {
@@ -221,21 +304,58 @@ impl<C: ClientState> WalkDirGeneric<C> {
     /// has to be infallible. Use [`try_into_iter()`][WalkDirGeneric::try_into_iter()]
     /// instead for error handling.
     pub fn new<P: AsRef<Path>>(root: P) -> Self {
+        Self::new_multi([root])
+    }
+
+    /// Create a builder for a recursive directory iterator over several roots.
+    ///
+    /// All roots are walked in one parallel run sharing the same thread pool.
+    /// Results are grouped per root: each root is yielded together with its
+    /// contents before the next root is started, in the order the roots were
+    /// given.
+    ///
+    /// Use [`dedupe_roots`](struct.WalkDirGeneric.html#method.dedupe_roots)
+    /// to avoid walking roots which are contained in other roots twice.
+    pub fn new_multi<I, P>(roots: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
         WalkDirGeneric {
-            root: root.as_ref().to_path_buf(),
+            roots: roots
+                .into_iter()
+                .map(|root| root.as_ref().to_path_buf())
+                .collect(),
             options: WalkDirOptions {
                 sort: false,
                 min_depth: 0,
-                max_depth: ::std::usize::MAX,
-                skip_hidden: true,
+                max_depth: usize::MAX,
+                hidden: Hidden::None,
+                filter_name: None,
+                entry_filter: EntryFilter::default(),
+                #[cfg(feature = "regex")]
+                match_regex: None,
+                #[cfg(feature = "regex")]
+                exclude_regex: None,
                 follow_links: false,
                 read_metadata: false,
                 read_metadata_ext: false,
+                dedupe_roots: false,
+                dedupe_inodes: false,
+                root_style: RootStyle::AsGiven,
                 parallelism: Parallelism::RayonDefaultPool {
                     busy_timeout: std::time::Duration::from_secs(1),
                 },
+                ordering: ResultOrdering::Strict,
+                retry_policy: RetryPolicy::default(),
+                resume_from: None,
                 root_read_dir_state: C::ReadDirState::default(),
                 process_read_dir: None,
+                process_entry: None,
+                content_hash: None,
+                process_entry_chunk_size: 64,
+                large_dir_threshold: 10_000,
+                fold_entries: None,
             },
         }
     }
@@ -250,9 +370,24 @@ impl<C: ClientState> WalkDirGeneric<C> {
         }
     }
 
-    /// Root path of the walk.
+    /// Root path of the walk. For walks created with
+    /// [`new_multi`](struct.WalkDirGeneric.html#method.new_multi) this is the
+    /// first root.
     pub fn root(&self) -> &Path {
-        &self.root
+        self.roots.first().map_or(Path::new(""), PathBuf::as_path)
+    }
+
+    /// Root paths of the walk.
+    pub fn roots(&self) -> &[PathBuf] {
+        &self.roots
+    }
+
+    /// Skip roots which are equal to or contained in another root, so that no
+    /// directory is walked twice. Roots are compared by their canonicalized
+    /// paths. Disabled by default.
+    pub fn dedupe_roots(mut self, dedupe_roots: bool) -> Self {
+        self.options.dedupe_roots = dedupe_roots;
+        self
     }
 
     /// Sort entries by `file_name` per directory. Defaults to `false`. Use
@@ -263,9 +398,175 @@ impl<C: ClientState> WalkDirGeneric<C> {
         self
     }
 
-    /// Skip hidden entries. Enabled by default.
+    /// How the root is presented in the paths of entries. Defaults to
+    /// [`RootStyle::AsGiven`](enum.RootStyle.html#variant.AsGiven).
+    /// [`DirEntry::relative_path`](struct.DirEntry.html#method.relative_path)
+    /// is available with every style.
+    pub fn root_style(mut self, root_style: RootStyle) -> Self {
+        self.options.root_style = root_style;
+        self
+    }
+
+    /// Skip dotfiles, entries whose name starts with a dot. Disabled by
+    /// default. Shorthand for
+    /// [`skip_hidden_by(Hidden::Dotfiles)`](struct.WalkDirGeneric.html#method.skip_hidden_by)
+    /// and `skip_hidden_by(Hidden::None)`.
     pub fn skip_hidden(mut self, skip_hidden: bool) -> Self {
-        self.options.skip_hidden = skip_hidden;
+        self.options.hidden = if skip_hidden {
+            Hidden::Dotfiles
+        } else {
+            Hidden::None
+        };
+        self
+    }
+
+    /// Skip entries considered hidden by `hidden`. Hidden entries are dropped
+    /// from the directory listing before their metadata is read, hidden
+    /// directories are not read at all. The roots of the walk are never
+    /// skipped.
+    pub fn skip_hidden_by(mut self, hidden: Hidden) -> Self {
+        self.options.hidden = hidden;
+        self
+    }
+
+    /// Filter entries by name, file type and depth while the directory is
+    /// listed. Return `false` to drop an entry; dropped directories are not
+    /// read. Use it for cheap exclusions like `node_modules` or `*.o`.
+    ///
+    /// Runs before an entry is constructed and before its metadata is read,
+    /// so dropped entries cost neither. The file type doesn't follow
+    /// symbolic links and on most platforms comes with the directory listing.
+    /// The roots of the walk are not filtered.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::WalkDir;
+    ///
+    /// let walk_dir = WalkDir::new("foo").filter_name(|file_name, file_type, _depth| {
+    ///     if file_type.is_dir() {
+    ///         file_name != "node_modules"
+    ///     } else {
+    ///         !file_name.as_encoded_bytes().ends_with(b".o")
+    ///     }
+    /// });
+    /// ```
+    pub fn filter_name<F>(mut self, filter_by: F) -> Self
+    where
+        F: Fn(&OsStr, FileType, usize) -> bool + Send + Sync + 'static,
+    {
+        self.options.filter_name = Some(Arc::new(filter_by));
+        self
+    }
+
+    /// Only yield entries matching any pattern of `regex_set`, applied to the
+    /// file name or the root relative path as selected by `target`.
+    ///
+    /// All patterns are compiled into one automaton, so matching costs about
+    /// the same for one or for hundreds of patterns. Files which don't match
+    /// are dropped before they are constructed; directories which don't match
+    /// are not yielded but still read, their contents may match. The roots of
+    /// the walk are not matched. Names are matched as bytes, non UTF-8 names
+    /// are supported. Requires the `regex` feature.
+    ///
+    /// ```no_run
+    /// # fn main() -> Result<(), jwalk_meta::regex::Error> {
+    /// use jwalk_meta::regex::bytes::RegexSet;
+    /// use jwalk_meta::{RegexTarget, WalkDir};
+    ///
+    /// let patterns = RegexSet::new([r"\.pem$", r"(^|/)id_rsa$", r"secret"])?;
+    /// let walk_dir = WalkDir::new("foo").match_regex(patterns, RegexTarget::RelativePath);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "regex")]
+    pub fn match_regex(mut self, regex_set: regex::bytes::RegexSet, target: RegexTarget) -> Self {
+        self.options.match_regex = Some(RegexFilter::new(regex_set, target));
+        self
+    }
+
+    /// Drop entries matching any pattern of `regex_set`, applied to the file
+    /// name or the root relative path as selected by `target`.
+    ///
+    /// Like [`filter_name`](struct.WalkDirGeneric.html#method.filter_name)
+    /// this runs while the directory is listed: excluded directories are not
+    /// read. See [`match_regex`](struct.WalkDirGeneric.html#method.match_regex)
+    /// for the matching rules. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    pub fn exclude_regex(mut self, regex_set: regex::bytes::RegexSet, target: RegexTarget) -> Self {
+        self.options.exclude_regex = Some(RegexFilter::new(regex_set, target));
+        self
+    }
+
+    /// Only yield entries of one of the given types.
+    ///
+    /// This and the other entry predicates
+    /// ([`min_size`](struct.WalkDirGeneric.html#method.min_size),
+    /// [`max_size`](struct.WalkDirGeneric.html#method.max_size),
+    /// [`modified_after`](struct.WalkDirGeneric.html#method.modified_after),
+    /// [`modified_before`](struct.WalkDirGeneric.html#method.modified_before),
+    /// [`owned_by`](struct.WalkDirGeneric.html#method.owned_by) and
+    /// [`permission_mask`](struct.WalkDirGeneric.html#method.permission_mask))
+    /// must all match for an entry to be yielded. Unlike
+    /// [`filter_name`](struct.WalkDirGeneric.html#method.filter_name) they
+    /// don't prune the walk: directories which don't match are still read.
+    /// Metadata needed by the predicates is read automatically, entries whose
+    /// metadata can't be read don't match.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::{EntryType, WalkDir};
+    ///
+    /// // Regular files of at least 1 MiB
+    /// let walk_dir = WalkDir::new("foo")
+    ///     .file_types(&[EntryType::File])
+    ///     .min_size(1024 * 1024);
+    /// ```
+    pub fn file_types(mut self, file_types: &[EntryType]) -> Self {
+        self.options.entry_filter.file_types = Some(file_types.to_vec());
+        self
+    }
+
+    /// Only yield entries of at least `size` bytes, see
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    pub fn min_size(mut self, size: u64) -> Self {
+        self.options.entry_filter.min_size = Some(size);
+        self
+    }
+
+    /// Only yield entries of at most `size` bytes, see
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    pub fn max_size(mut self, size: u64) -> Self {
+        self.options.entry_filter.max_size = Some(size);
+        self
+    }
+
+    /// Only yield entries modified after `time`, see
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    pub fn modified_after(mut self, time: SystemTime) -> Self {
+        self.options.entry_filter.modified_after = Some(time);
+        self
+    }
+
+    /// Only yield entries modified before `time`, see
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    pub fn modified_before(mut self, time: SystemTime) -> Self {
+        self.options.entry_filter.modified_before = Some(time);
+        self
+    }
+
+    /// Only yield entries owned by the user `uid`, see
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    #[cfg(unix)]
+    pub fn owned_by(mut self, uid: u32) -> Self {
+        self.options.entry_filter.owned_by = Some(uid);
+        self
+    }
+
+    /// Only yield entries with all bits of `mask` set in their mode, like
+    /// `find -perm -MODE`. For example `0o111` matches entries executable by
+    /// everyone. See
+    /// [`file_types`](struct.WalkDirGeneric.html#method.file_types).
+    #[cfg(unix)]
+    pub fn permission_mask(mut self, mask: u32) -> Self {
+        self.options.entry_filter.permission_mask = Some(mask);
         self
     }
 
@@ -335,6 +636,23 @@ impl<C: ClientState> WalkDirGeneric<C> {
         self
     }
 
+    /// Remember the `(device, inode)` pair of every directory and every file
+    /// with more than one hard link across all parallel workers. Later
+    /// occurrences of an already seen file are marked with
+    /// [`DirEntry::duplicate_inode`](struct.DirEntry.html#structfield.duplicate_inode)
+    /// and duplicate directories are not descended. This keeps hard links from
+    /// being counted twice and stops walks that follow links from entering the
+    /// same directory twice. Which occurrence is seen first depends on thread
+    /// scheduling unless `parallelism` is `Serial`.
+    ///
+    /// Enables [`read_metadata`](struct.WalkDirGeneric.html#method.read_metadata)
+    /// and [`read_metadata_ext`](struct.WalkDirGeneric.html#method.read_metadata_ext).
+    /// Disabled by default.
+    pub fn dedupe_inodes(mut self, dedupe_inodes: bool) -> Self {
+        self.options.dedupe_inodes = dedupe_inodes;
+        self
+    }
+
     /// Degree of parallelism to use when performing walk. Defaults to
     /// [`Parallelism::RayonDefaultPool`](enum.Parallelism.html#variant.RayonDefaultPool).
     pub fn parallelism(mut self, parallelism: Parallelism) -> Self {
@@ -342,6 +660,67 @@ impl<C: ClientState> WalkDirGeneric<C> {
         self
     }
 
+    /// Order in which results are yielded when walking in parallel. Defaults
+    /// to [`ResultOrdering::Strict`](enum.ResultOrdering.html#variant.Strict),
+    /// ignored with [`Parallelism::Serial`](enum.Parallelism.html#variant.Serial).
+    ///
+    /// With [`ResultOrdering::Relaxed`](enum.ResultOrdering.html#variant.Relaxed)
+    /// the entries of a directory follow each other as read, sorted if
+    /// [`sort`](struct.WalkDirGeneric.html#method.sort) is enabled, but
+    /// directories are yielded in the order the workers finish them, and the
+    /// contents of a directory don't follow the directory entry directly.
+    /// Errors reading a directory are yielded as separate items instead of
+    /// being stored in
+    /// [`read_children_error`](struct.DirEntry.html#structfield.read_children_error),
+    /// retries needed to open a directory are not added to
+    /// [`retries`](struct.DirEntry.html#structfield.retries) and
+    /// [`DirEntryIter::checkpoint`](struct.DirEntryIter.html#method.checkpoint)
+    /// is not available.
+    pub fn ordering(mut self, ordering: ResultOrdering) -> Self {
+        self.options.ordering = ordering;
+        self
+    }
+
+    /// Policy for retrying transient I/O errors when opening directories and
+    /// reading metadata. Defaults to
+    /// [`RetryPolicy::default()`](struct.RetryPolicy.html), which never
+    /// retries.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.options.retry_policy = retry_policy;
+        self
+    }
+
+    /// Resume a walk after the position of `checkpoint`, taken with
+    /// [`DirEntryIter::checkpoint`](struct.DirEntryIter.html#method.checkpoint)
+    /// from an earlier walk over the same roots.
+    ///
+    /// Entries up to and including the checkpoint entry are not yielded
+    /// again, the walk continues in the same order. Resuming relies on the
+    /// order by file name, so this enables
+    /// [`sort`](struct.WalkDirGeneric.html#method.sort). A
+    /// [`process_read_dir`](struct.WalkDirGeneric.html#method.process_read_dir)
+    /// callback must not reorder entries, it sees the finished entries too.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::WalkDir;
+    ///
+    /// let mut iter = WalkDir::new("foo").sort(true).into_iter();
+    /// for dir_entry in iter.by_ref().take(1000) {
+    ///     println!("{}", dir_entry.unwrap().path().display());
+    /// }
+    /// let checkpoint = iter.checkpoint().unwrap();
+    /// drop(iter);
+    ///
+    /// for dir_entry in WalkDir::new("foo").resume_from(checkpoint) {
+    ///     println!("{}", dir_entry.unwrap().path().display());
+    /// }
+    /// ```
+    pub fn resume_from(mut self, checkpoint: Checkpoint) -> Self {
+        self.options.sort = true;
+        self.options.resume_from = Some(checkpoint);
+        self
+    }
+
     /// Initial ClientState::ReadDirState that is passed to
     /// [`process_read_dir`](struct.WalkDirGeneric.html#method.process_read_dir)
     /// when processing root. Defaults to ClientState::ReadDirState::default().
@@ -367,16 +746,217 @@ impl<C: ClientState> WalkDirGeneric<C> {
         self.options.process_read_dir = Some(Arc::new(process_by));
         self
     }
+
+    /// Number of entries from which the entries of a single directory are
+    /// processed in parallel: metadata is read, entries are constructed,
+    /// filtered and sorted on all workers of the pool. The order of the
+    /// results is the same as for smaller directories. Defaults to 10000,
+    /// ignored with [`Parallelism::Serial`](enum.Parallelism.html#variant.Serial).
+    pub fn large_dir_threshold(mut self, threshold: usize) -> Self {
+        self.options.large_dir_threshold = threshold;
+        self
+    }
+
+    /// A callback function to process single entries before they are yielded,
+    /// for example to hash the contents of files, sniff their type or read a
+    /// header into [`entry.client_state`](struct.DirEntry.html#field.client_state).
+    ///
+    /// Runs on the worker threads after
+    /// [`process_read_dir`](struct.WalkDirGeneric.html#method.process_read_dir).
+    /// Unlike `process_read_dir` the entries of a single directory are
+    /// processed in parallel, in chunks of
+    /// [`process_entry_chunk_size`](struct.WalkDirGeneric.html#method.process_entry_chunk_size)
+    /// entries. The order of the yielded entries is not affected. With
+    /// [`Parallelism::Serial`](enum.Parallelism.html#variant.Serial) entries
+    /// are processed one after another.
+    pub fn process_entry<F>(mut self, process_by: F) -> Self
+    where
+        F: Fn(&mut DirEntry<C>) + Send + Sync + 'static,
+    {
+        self.options.process_entry = Some(Arc::new(process_by));
+        self
+    }
+
+    /// Compute a digest of the contents of every regular file, stored in
+    /// [`entry.content_hash`](struct.DirEntry.html#field.content_hash).
+    ///
+    /// Files are hashed on the worker threads like
+    /// [`process_entry`](struct.WalkDirGeneric.html#method.process_entry),
+    /// before `process_entry` runs. Errors reading a file are stored in
+    /// [`entry.content_hash_error`](struct.DirEntry.html#field.content_hash_error).
+    ///
+    /// ```no_run
+    /// # #[cfg(feature = "blake3")]
+    /// # fn main() {
+    /// use jwalk_meta::{ContentHash, HashAlgorithm, WalkDir};
+    ///
+    /// let content_hash = ContentHash {
+    ///     max_size: Some(1 << 30),
+    ///     partial_kib: Some(64),
+    ///     ..ContentHash::new(HashAlgorithm::Blake3)
+    /// };
+    /// for entry in WalkDir::new("foo").content_hash(content_hash) {
+    ///     let entry = entry.unwrap();
+    ///     println!("{:?} {:?}", entry.content_hash, entry.path());
+    /// }
+    /// # }
+    /// # #[cfg(not(feature = "blake3"))]
+    /// # fn main() {}
+    /// ```
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    pub fn content_hash(mut self, content_hash: ContentHash) -> Self {
+        self.options.content_hash = Some(Arc::new(move |dir_entry| {
+            content_hash.hash_entry(dir_entry)
+        }));
+        self
+    }
+
+    /// Minimum number of entries of a directory processed as one task by
+    /// [`process_entry`](struct.WalkDirGeneric.html#method.process_entry).
+    /// Smaller directories are processed on a single thread. Defaults to 64.
+    pub fn process_entry_chunk_size(mut self, chunk_size: usize) -> Self {
+        self.options.process_entry_chunk_size = chunk_size.max(1);
+        self
+    }
+
+    /// Compute a value per directory from its subtree, bottom-up.
+    ///
+    /// Each directory starts with `init()`. `per_entry` folds every child
+    /// entry (files and subdirectories alike) into the value of its parent
+    /// directory; it runs on the worker thread that read the directory, after
+    /// [`process_read_dir`](struct.WalkDirGeneric.html#method.process_read_dir).
+    /// Once all descendants of a directory are done, `merge` folds its value
+    /// into the value of its parent and the directory is yielded together with
+    /// its value. The root is yielded last.
+    ///
+    /// Typical values are total size, file count, newest modification time or
+    /// a hash over the subtree. Only the directories along the current path
+    /// are held in memory, the walk itself is streamed.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::WalkDir;
+    ///
+    /// for result in WalkDir::new("foo").read_metadata(true).fold_dirs(
+    ///     || 0u64,
+    ///     |size, entry| *size += entry.metadata.as_ref().map_or(0, |m| m.size),
+    ///     |size, child_size| *size += child_size,
+    /// ) {
+    ///     let (dir_entry, size) = result.unwrap();
+    ///     println!("{}\t{}", size, dir_entry.path().display());
+    /// }
+    /// ```
+    ///
+    /// [`min_depth`](struct.WalkDirGeneric.html#method.min_depth) is ignored
+    /// because every directory contributes to its parent. Entry predicates
+    /// such as [`file_types`](struct.WalkDirGeneric.html#method.file_types)
+    /// select the entries passed to `per_entry`, directories not matching
+    /// them are still yielded with the values of their contents.
+    pub fn fold_dirs<T, I, F, M>(mut self, init: I, per_entry: F, merge: M) -> FoldDirsIter<C, T>
+    where
+        T: Send + 'static,
+        I: Fn() -> T + Send + Sync + 'static,
+        F: Fn(&mut T, &DirEntry<C>) + Send + Sync + 'static,
+        M: FnMut(&mut T, &T) + 'static,
+    {
+        let init = Arc::new(init);
+        let fold_init = init.clone();
+        self.options.fold_entries = Some(Arc::new(move |dir_entry_results| {
+            let mut value = fold_init();
+            for dir_entry in dir_entry_results.iter().flatten() {
+                if !dir_entry.skip_yield {
+                    per_entry(&mut value, dir_entry);
+                }
+            }
+            Box::new(value)
+        }));
+        self.options.min_depth = 0;
+        self.options.ordering = ResultOrdering::Strict;
+        FoldDirsIter::new(self.into_iter(), init, Box::new(merge))
+    }
+
+    /// Render the walk as an indented tree drawn with box-drawing characters,
+    /// similar to the `tree` command.
+    ///
+    /// Lines are streamed as the walk proceeds, nothing is buffered beyond
+    /// the directories along the current path. Enable
+    /// [`sort`](struct.WalkDirGeneric.html#method.sort) for the usual
+    /// alphabetical order.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::{TreeOptions, WalkDir};
+    ///
+    /// let options = TreeOptions {
+    ///     sizes: true,
+    ///     max_depth: 2,
+    ///     ..TreeOptions::default()
+    /// };
+    /// for line in WalkDir::new("foo").sort(true).tree(options) {
+    ///     println!("{}", line.unwrap());
+    /// }
+    /// ```
+    ///
+    /// Metadata is read automatically if sizes, dates or totals are shown.
+    /// [`min_depth`](struct.WalkDirGeneric.html#method.min_depth) is ignored.
+    pub fn tree(mut self, options: TreeOptions) -> TreeIter<C> {
+        if options.sizes || options.dates || options.dir_totals {
+            self.options.read_metadata = true;
+        }
+        if !options.dir_totals {
+            self.options.max_depth = self.options.max_depth.min(options.max_depth);
+        }
+        self.options.min_depth = 0;
+        self.options.ordering = ResultOrdering::Strict;
+        TreeIter::new(self.into_iter(), options)
+    }
+
+    /// Walk the tree as rayon parallel iterator, for consumers that don't
+    /// need the entries in order.
+    ///
+    /// Entries are handed to the consumer on the worker thread that read
+    /// their directory, without the reordering on the calling thread that
+    /// [`into_iter`](struct.WalkDirGeneric.html#method.into_iter) performs.
+    /// Parents are still produced before their children within a worker,
+    /// but no order between entries is guaranteed otherwise.
+    ///
+    /// ```no_run
+    /// use jwalk_meta::rayon::prelude::*;
+    /// use jwalk_meta::WalkDir;
+    ///
+    /// let total: u64 = WalkDir::new("foo")
+    ///     .read_metadata(true)
+    ///     .into_par_iter()
+    ///     .filter_map(|dir_entry| dir_entry.ok()?.metadata.map(|m| m.size))
+    ///     .sum();
+    /// ```
+    ///
+    /// The walk runs in the thread pool the parallel iterator is consumed in,
+    /// [`parallelism`](struct.WalkDirGeneric.html#method.parallelism) is
+    /// ignored. Use `ThreadPool::install` to pick a pool. Errors reading a
+    /// directory are yielded as separate items instead of being stored in
+    /// [`read_children_error`](struct.DirEntry.html#structfield.read_children_error),
+    /// and retries needed to open a directory are not added to
+    /// [`retries`](struct.DirEntry.html#structfield.retries) of the
+    /// directory entry.
+    pub fn into_par_iter(self) -> impl ParallelIterator<Item = Result<DirEntry<C>>> {
+        let setup = self.into_walk_setup();
+        par_iter(
+            setup.root_entry_results,
+            setup.min_depth,
+            setup.root_read_dir_state,
+            setup.read_dir_callback,
+        )
+    }
 }
 
 fn process_dir_entry_result<C: ClientState>(
     dir_entry_result: Result<DirEntry<C>>,
     follow_links: bool,
+    retry_policy: &RetryPolicy,
 ) -> Result<DirEntry<C>> {
     match dir_entry_result {
         Ok(mut dir_entry) => {
             if follow_links && dir_entry.file_type.is_symlink() {
-                dir_entry = dir_entry.follow_symlink()?;
+                dir_entry = dir_entry.follow_symlink(retry_policy)?;
             }
 
             if dir_entry.depth == 0 && dir_entry.file_type.is_symlink() {
@@ -387,10 +967,10 @@ fn process_dir_entry_result<C: ClientState>(
                 // respect the follow_links setting. When it's disabled, it
                 // should report itself as a symlink. When it's enabled, it
                 // should always report itself as the target.
-                let metadata = fs::metadata(dir_entry.path())
-                    .map_err(|err| Error::from_path(0, dir_entry.path(), err))?;
+                let metadata = fs::metadata(dir_entry.full_path())
+                    .map_err(|err| Error::from_path(0, dir_entry.full_path(), err))?;
                 if metadata.file_type().is_dir() {
-                    dir_entry.read_children_path = Some(Arc::from(dir_entry.path()));
+                    dir_entry.read_children_path = Some(dir_entry.shared_full_path());
                 }
             }
 
@@ -400,60 +980,162 @@ fn process_dir_entry_result<C: ClientState>(
     }
 }
 
-impl<C: ClientState> IntoIterator for WalkDirGeneric<C> {
-    type Item = Result<DirEntry<C>>;
-    type IntoIter = DirEntryIter<C>;
+// Root entries and read_dir callback of a walk, shared by the sequential and
+// the parallel iterator.
+struct WalkSetup<C: ClientState> {
+    root_entry_results: Vec<Result<DirEntry<C>>>,
+    root_read_dir_state: C::ReadDirState,
+    parallelism: Parallelism,
+    ordering: ResultOrdering,
+    min_depth: usize,
+    read_dir_callback: Arc<ReadDirCallback<C>>,
+}
 
-    fn into_iter(self) -> DirEntryIter<C> {
+impl<C: ClientState> WalkDirGeneric<C> {
+    fn into_walk_setup(self) -> WalkSetup<C> {
         let sort = self.options.sort;
         let max_depth = self.options.max_depth;
         let min_depth = self.options.min_depth;
         let parallelism = self.options.parallelism;
-        let skip_hidden = self.options.skip_hidden;
+        let ordering = self.options.ordering;
+        let hidden = self.options.hidden;
+        let filter_name = self.options.filter_name;
+        let entry_filter = self.options.entry_filter;
+        #[cfg(feature = "regex")]
+        let match_regex = self.options.match_regex;
+        #[cfg(feature = "regex")]
+        let exclude_regex = self.options.exclude_regex;
         let follow_links = self.options.follow_links;
-        let read_metadata = self.options.read_metadata;
-        let read_metadata_ext = self.options.read_metadata_ext;
+        // Entries which might be read as directory
+        #[cfg(feature = "regex")]
+        let might_descend =
+            move |file_type: FileType| file_type.is_dir() || follow_links && file_type.is_symlink();
+        let dedupe_inodes = self.options.dedupe_inodes;
+        let strip_root = self.options.root_style == RootStyle::Stripped;
+        let read_metadata_ext =
+            self.options.read_metadata_ext || dedupe_inodes || entry_filter.needs_metadata_ext();
+        let read_metadata =
+            self.options.read_metadata || dedupe_inodes || entry_filter.needs_metadata();
+        let seen_inodes = Arc::new(Mutex::new(HashSet::new()));
+        let retry_policy = self.options.retry_policy;
+        let resume_from = self.options.resume_from;
         let process_read_dir = self.options.process_read_dir.clone();
+        let process_entry = match (self.options.content_hash, self.options.process_entry) {
+            (Some(content_hash), Some(process_entry)) => {
+                let process_both: Arc<ProcessEntryFunction<C>> = Arc::new(move |dir_entry| {
+                    content_hash(dir_entry);
+                    process_entry(dir_entry);
+                });
+                Some(process_both)
+            }
+            (content_hash, process_entry) => content_hash.or(process_entry),
+        };
+        let process_entry_chunk_size = self.options.process_entry_chunk_size;
+        let process_entry_parallel = !matches!(parallelism, Parallelism::Serial);
+        let parallel_large_dirs = process_entry_parallel;
+        let large_dir_threshold = self.options.large_dir_threshold;
+        let fold_entries = self.options.fold_entries.clone();
         let mut root_read_dir_state = self.options.root_read_dir_state;
-        let follow_link_ancestors = if follow_links {
-            Arc::new(vec![Arc::from(self.root.clone()) as Arc<Path>])
+        let mut roots = if self.options.dedupe_roots {
+            dedupe_roots(self.roots)
         } else {
-            Arc::new(vec![])
+            self.roots
         };
+        if self.options.root_style == RootStyle::Canonical {
+            // Roots which can't be canonicalized fail with their own error below
+            for root in roots.iter_mut() {
+                if let Ok(canonical_root) = fs::canonicalize(&root) {
+                    *root = canonical_root;
+                }
+            }
+        }
+
+        let mut root_entry_results = Vec::with_capacity(roots.len());
+        for root in roots {
+            let follow_link_ancestors = if follow_links {
+                Arc::new(vec![Arc::new(FollowLinkAncestor::new(
+                    Arc::from(root.clone()),
+                    None,
+                ))])
+            } else {
+                Arc::new(vec![])
+            };
 
-        let root_entry = DirEntry::from_path(
-            0,
-            &self.root,
-            read_metadata,
-            read_metadata_ext,
-            false,
-            follow_link_ancestors,
-        );
-        let root_parent_path = root_entry
-            .as_ref()
-            .map(|root| root.parent_path().to_owned())
-            .unwrap_or_default();
-        let mut root_entry_results = vec![process_dir_entry_result(root_entry, follow_links)];
-        if let Some(process_read_dir) = process_read_dir.as_ref() {
-            process_read_dir(
-                None,
-                &root_parent_path,
-                &mut root_read_dir_state,
-                &mut root_entry_results,
+            let root_entry = DirEntry::from_path_with_retry(
+                0,
+                &root,
+                read_metadata,
+                read_metadata_ext,
+                false,
+                follow_link_ancestors,
+                &retry_policy,
             );
+            let root_parent_path = root_entry
+                .as_ref()
+                .map(|root| root.parent_path().to_owned())
+                .unwrap_or_default();
+            let mut root_result = process_dir_entry_result(root_entry, follow_links, &retry_policy);
+            if let Ok(root_entry) = root_result.as_mut() {
+                root_entry.strip_root = strip_root;
+                if dedupe_inodes {
+                    mark_duplicate_inode(root_entry, &seen_inodes);
+                }
+                if !entry_filter.matches(root_entry) {
+                    root_entry.skip_yield = true;
+                }
+            }
+            let mut root_results = vec![root_result];
+            if let Some(process_read_dir) = process_read_dir.as_ref() {
+                process_read_dir(
+                    None,
+                    &root_parent_path,
+                    &mut root_read_dir_state,
+                    &mut root_results,
+                );
+            }
+            if let Some(process_entry) = process_entry.as_ref() {
+                for root_entry in root_results.iter_mut().flatten() {
+                    process_entry(root_entry);
+                }
+            }
+            root_entry_results.extend(root_results);
         }
 
-        DirEntryIter::new(
+        if let Some(checkpoint) = resume_from {
+            for (index, root_result) in root_entry_results.iter_mut().enumerate() {
+                let Ok(root_entry) = root_result else {
+                    continue;
+                };
+                match index.cmp(&checkpoint.root) {
+                    Ordering::Less => {
+                        root_entry.skip_yield = true;
+                        root_entry.read_children_path = None;
+                    }
+                    Ordering::Equal => {
+                        root_entry.skip_yield = true;
+                        if !checkpoint.names.is_empty() {
+                            root_entry.resume_cursor = Some(Arc::from(checkpoint.names.clone()));
+                        }
+                    }
+                    Ordering::Greater => {}
+                }
+            }
+        }
+
+        WalkSetup {
             root_entry_results,
+            root_read_dir_state,
             parallelism,
+            ordering,
             min_depth,
-            root_read_dir_state,
-            Arc::new(move |read_dir_spec| {
+            read_dir_callback: Arc::new(move |read_dir_spec| {
                 let ReadDirSpec {
                     path,
                     depth,
                     mut client_read_state,
                     mut follow_link_ancestors,
+                    inode_key,
+                    resume_cursor,
                 } = read_dir_spec;
 
                 let read_dir_depth = depth;
@@ -466,82 +1148,210 @@ impl<C: ClientState> IntoIterator for WalkDirGeneric<C> {
                 follow_link_ancestors = if follow_links {
                     let mut ancestors = Vec::with_capacity(follow_link_ancestors.len() + 1);
                     ancestors.extend(follow_link_ancestors.iter().cloned());
-                    ancestors.push(path.clone());
+                    ancestors.push(Arc::new(FollowLinkAncestor::new(path.clone(), inode_key)));
                     Arc::new(ancestors)
                 } else {
                     follow_link_ancestors
                 };
 
-                let mut dir_entry_results: Vec<_> = fs::read_dir(path.as_ref())
+                #[cfg(feature = "regex")]
+                let relative_dir = if match_regex
+                    .iter()
+                    .chain(exclude_regex.iter())
+                    .any(RegexFilter::needs_relative_path)
+                {
+                    relative_dir(&path, read_dir_depth)
+                } else {
+                    Vec::new()
+                };
+
+                let (read_dir_result, read_dir_retries) =
+                    retry_policy.run(|| fs::read_dir(path.as_ref()));
+                let fs_dir_entries: Vec<_> = read_dir_result
                     .map_err(|err| Error::from_path(0, path.to_path_buf(), err))?
-                    .filter_map(|dir_entry_result| {
-                        let fs_dir_entry = match dir_entry_result {
-                            Ok(fs_dir_entry) => fs_dir_entry,
-                            Err(err) => {
-                                return Some(Err(Error::from_io(read_dir_contents_depth, err)))
-                            }
+                    .filter(|dir_entry_result| {
+                        let Ok(fs_dir_entry) = dir_entry_result else {
+                            return true;
                         };
-
-                        let mut entry_metadata = None;
-                        let mut entry_metadata_ext = None;
-                        if read_metadata {
-                            if let Ok(metadata) = fs_dir_entry.metadata() {
-                                entry_metadata = Some(MetaData {
-                                    is_dir: metadata.is_dir(),
-                                    is_file: metadata.is_file(),
-                                    is_symlink: metadata.is_symlink(),
-                                    size: metadata.len(),
-                                    created: metadata.created().ok(),
-                                    modified: metadata.modified().ok(),
-                                    accessed: metadata.accessed().ok(),
-                                    permissions: Some(metadata.permissions()),
-                                });
-                            } else if let Ok(file_type) = fs_dir_entry.file_type() {
-                                entry_metadata = Some(MetaData {
-                                    is_dir: file_type.is_dir(),
-                                    is_file: file_type.is_file(),
-                                    is_symlink: file_type.is_symlink(),
-                                    size: 0,
-                                    created: None,
-                                    modified: None,
-                                    accessed: None,
-                                    permissions: None,
-                                });
+                        if hidden.is_hidden(fs_dir_entry) {
+                            return false;
+                        }
+                        #[cfg(feature = "regex")]
+                        if match_regex.is_some() || exclude_regex.is_some() {
+                            let file_name = fs_dir_entry.file_name();
+                            if exclude_regex.as_ref().is_some_and(|exclude_regex| {
+                                exclude_regex.is_match(&relative_dir, &file_name)
+                            }) {
+                                return false;
                             }
-                            if read_metadata_ext {
-                                if let Ok(metadata) = fs::metadata(fs_dir_entry.path()) {
-                                    entry_metadata_ext = Some(get_metadata_ext(&metadata));
-                                }
+                            // Directories which don't match are checked again
+                            // once they are constructed
+                            if match_regex.as_ref().is_some_and(|match_regex| {
+                                !match_regex.is_match(&relative_dir, &file_name)
+                                    && !fs_dir_entry.file_type().is_ok_and(might_descend)
+                            }) {
+                                return false;
                             }
                         }
+                        match (filter_name.as_ref(), fs_dir_entry.file_type()) {
+                            (Some(filter_name), Ok(file_type)) => filter_name(
+                                &fs_dir_entry.file_name(),
+                                file_type,
+                                read_dir_contents_depth,
+                            ),
+                            _ => true,
+                        }
+                    })
+                    .collect();
+                let large_dir = parallel_large_dirs && fs_dir_entries.len() >= large_dir_threshold;
+                let prefetched_metadata = if read_metadata {
+                    PrefetchedMetadata::read(&path, &fs_dir_entries)
+                } else {
+                    None
+                };
 
-                        let dir_entry = match DirEntry::from_entry(
-                            read_dir_contents_depth,
-                            path.clone(),
-                            entry_metadata,
-                            entry_metadata_ext,
-                            &fs_dir_entry,
-                            follow_link_ancestors.clone(),
-                        ) {
-                            Ok(dir_entry) => dir_entry,
-                            Err(err) => return Some(Err(err)),
-                        };
+                let build_dir_entry = |(index, dir_entry_result): (
+                    usize,
+                    io::Result<fs::DirEntry>,
+                )|
+                 -> Option<Result<DirEntry<C>>> {
+                    let fs_dir_entry = match dir_entry_result {
+                        Ok(fs_dir_entry) => fs_dir_entry,
+                        Err(err) => return Some(Err(Error::from_io(read_dir_contents_depth, err))),
+                    };
 
-                        if skip_hidden && is_hidden(&dir_entry.file_name) {
-                            return None;
+                    let mut entry_metadata = None;
+                    let mut entry_metadata_ext = None;
+                    let mut retries = 0;
+                    let prefetched = prefetched_metadata
+                        .as_ref()
+                        .and_then(|prefetched_metadata| prefetched_metadata.get(index));
+                    if let Some((metadata, metadata_ext)) = prefetched {
+                        entry_metadata = Some(metadata);
+                        if read_metadata_ext {
+                            entry_metadata_ext = metadata_ext;
                         }
+                    } else if read_metadata {
+                        let (metadata_result, metadata_retries) =
+                            retry_policy.run(|| fs_dir_entry.metadata());
+                        retries += metadata_retries;
+                        if let Ok(metadata) = metadata_result {
+                            entry_metadata = Some(MetaData {
+                                is_dir: metadata.is_dir(),
+                                is_file: metadata.is_file(),
+                                is_symlink: metadata.is_symlink(),
+                                size: metadata.len(),
+                                created: metadata.created().ok(),
+                                modified: metadata.modified().ok(),
+                                accessed: metadata.accessed().ok(),
+                                permissions: Some(metadata.permissions()),
+                            });
+                        } else if let Ok(file_type) = fs_dir_entry.file_type() {
+                            entry_metadata = Some(MetaData {
+                                is_dir: file_type.is_dir(),
+                                is_file: file_type.is_file(),
+                                is_symlink: file_type.is_symlink(),
+                                size: 0,
+                                created: None,
+                                modified: None,
+                                accessed: None,
+                                permissions: None,
+                            });
+                        }
+                    }
+                    if read_metadata && read_metadata_ext && entry_metadata_ext.is_none() {
+                        let (metadata_result, metadata_retries) =
+                            retry_policy.run(|| fs::metadata(fs_dir_entry.path()));
+                        retries += metadata_retries;
+                        if let Ok(metadata) = metadata_result {
+                            entry_metadata_ext = Some(get_metadata_ext(&metadata));
+                        }
+                    }
 
-                        Some(process_dir_entry_result(Ok(dir_entry), follow_links))
-                    })
-                    .collect();
+                    let mut dir_entry = match DirEntry::from_entry(
+                        read_dir_contents_depth,
+                        path.clone(),
+                        entry_metadata,
+                        entry_metadata_ext,
+                        &fs_dir_entry,
+                        follow_link_ancestors.clone(),
+                    ) {
+                        Ok(dir_entry) => dir_entry,
+                        Err(err) => return Some(Err(err)),
+                    };
+
+                    dir_entry.retries = retries;
+
+                    let mut dir_entry = match process_dir_entry_result(
+                        Ok(dir_entry),
+                        follow_links,
+                        &retry_policy,
+                    ) {
+                        Ok(dir_entry) => dir_entry,
+                        Err(err) => return Some(Err(err)),
+                    };
+
+                    dir_entry.strip_root = strip_root;
+                    if dedupe_inodes {
+                        mark_duplicate_inode(&mut dir_entry, &seen_inodes);
+                    }
+
+                    // Entries not matching the predicates are only kept to
+                    // descend into them
+                    if !entry_filter.is_empty() && !entry_filter.matches(&dir_entry) {
+                        match dir_entry.read_children_path {
+                            Some(_) => dir_entry.skip_yield = true,
+                            None => return None,
+                        }
+                    }
+                    #[cfg(feature = "regex")]
+                    if let Some(match_regex) = match_regex.as_ref() {
+                        if fs_dir_entry.file_type().is_ok_and(might_descend)
+                            && !match_regex.is_match(&relative_dir, &dir_entry.file_name)
+                        {
+                            match dir_entry.read_children_path {
+                                Some(_) => dir_entry.skip_yield = true,
+                                None => return None,
+                            }
+                        }
+                    }
+
+                    Some(Ok(dir_entry))
+                };
+
+                // Stat calls and entry construction of large directories are
+                // split across the workers, collecting keeps the read order.
+                let mut dir_entry_results: Vec<_> = if large_dir {
+                    fs_dir_entries
+                        .into_par_iter()
+                        .enumerate()
+                        .with_min_len(LARGE_DIR_CHUNK_SIZE)
+                        .filter_map(build_dir_entry)
+                        .collect()
+                } else {
+                    fs_dir_entries
+                        .into_iter()
+                        .enumerate()
+                        .filter_map(build_dir_entry)
+                        .collect()
+                };
 
                 if sort {
-                    dir_entry_results.sort_by(|a, b| match (a, b) {
+                    let compare = |a: &Result<DirEntry<C>>, b: &Result<DirEntry<C>>| match (a, b) {
                         (Ok(a), Ok(b)) => a.file_name.cmp(&b.file_name),
                         (Ok(_), Err(_)) => Ordering::Less,
                         (Err(_), Ok(_)) => Ordering::Greater,
                         (Err(_), Err(_)) => Ordering::Equal,
-                    });
+                    };
+                    if large_dir {
+                        dir_entry_results.par_sort_by(compare);
+                    } else {
+                        dir_entry_results.sort_by(compare);
+                    }
+                }
+
+                if let Some(resume_cursor) = resume_cursor.as_ref() {
+                    apply_resume_cursor(&mut dir_entry_results, resume_cursor);
                 }
 
                 if let Some(process_read_dir) = process_read_dir.as_ref() {
@@ -553,8 +1363,49 @@ impl<C: ClientState> IntoIterator for WalkDirGeneric<C> {
                     );
                 }
 
-                Ok(ReadDir::new(client_read_state, dir_entry_results))
+                if let Some(process_entry) = process_entry.as_ref() {
+                    let process = |dir_entry_result: &mut Result<DirEntry<C>>| {
+                        if let Ok(dir_entry) = dir_entry_result {
+                            if !dir_entry.skip_yield {
+                                process_entry(dir_entry);
+                            }
+                        }
+                    };
+                    if process_entry_parallel && dir_entry_results.len() > process_entry_chunk_size
+                    {
+                        dir_entry_results
+                            .par_iter_mut()
+                            .with_min_len(process_entry_chunk_size)
+                            .for_each(process);
+                    } else {
+                        dir_entry_results.iter_mut().for_each(process);
+                    }
+                }
+
+                let mut read_dir = ReadDir::new(client_read_state, dir_entry_results);
+                read_dir.retries = read_dir_retries;
+                read_dir.fold_value = fold_entries
+                    .as_ref()
+                    .map(|fold_entries| fold_entries(&read_dir.results_list));
+                Ok(read_dir)
             }),
+        }
+    }
+}
+
+impl<C: ClientState> IntoIterator for WalkDirGeneric<C> {
+    type Item = Result<DirEntry<C>>;
+    type IntoIter = DirEntryIter<C>;
+
+    fn into_iter(self) -> DirEntryIter<C> {
+        let setup = self.into_walk_setup();
+        DirEntryIter::new(
+            setup.root_entry_results,
+            setup.parallelism,
+            setup.ordering,
+            setup.min_depth,
+            setup.root_read_dir_state,
+            setup.read_dir_callback,
         )
     }
 }
@@ -565,13 +1416,30 @@ impl<C: ClientState> Clone for WalkDirOptions<C> {
             sort: false,
             min_depth: self.min_depth,
             max_depth: self.max_depth,
-            skip_hidden: self.skip_hidden,
+            hidden: self.hidden.clone(),
+            filter_name: self.filter_name.clone(),
+            entry_filter: self.entry_filter.clone(),
+            #[cfg(feature = "regex")]
+            match_regex: self.match_regex.clone(),
+            #[cfg(feature = "regex")]
+            exclude_regex: self.exclude_regex.clone(),
             follow_links: self.follow_links,
             read_metadata: self.read_metadata,
             read_metadata_ext: self.read_metadata_ext,
+            dedupe_roots: self.dedupe_roots,
+            dedupe_inodes: self.dedupe_inodes,
+            root_style: self.root_style,
             parallelism: self.parallelism.clone(),
+            ordering: self.ordering,
+            retry_policy: self.retry_policy.clone(),
+            resume_from: self.resume_from.clone(),
             root_read_dir_state: self.root_read_dir_state.clone(),
             process_read_dir: self.process_read_dir.clone(),
+            process_entry: self.process_entry.clone(),
+            content_hash: self.content_hash.clone(),
+            process_entry_chunk_size: self.process_entry_chunk_size,
+            large_dir_threshold: self.large_dir_threshold,
+            fold_entries: self.fold_entries.clone(),
         }
     }
 }
@@ -599,6 +1467,51 @@ impl Parallelism {
         }
     }
 
+    // Build the pool of `RayonNewPool` now, so that the walk and later
+    // `install` calls share it instead of building a pool each.
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    pub(crate) fn into_existing_pool(self) -> Parallelism {
+        match self {
+            Parallelism::RayonNewPool(num_threads) => {
+                let mut thread_pool = ThreadPoolBuilder::new();
+                if num_threads > 0 {
+                    thread_pool = thread_pool.num_threads(num_threads);
+                }
+                match thread_pool.build() {
+                    Ok(thread_pool) => Parallelism::RayonExistingPool {
+                        pool: Arc::new(thread_pool),
+                        busy_timeout: None,
+                    },
+                    Err(_) => Parallelism::RayonNewPool(num_threads),
+                }
+            }
+            parallelism => parallelism,
+        }
+    }
+
+    // Run `op` in the thread pool, so parallel iterators inside use it.
+    #[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+    pub(crate) fn install<OP, R>(&self, op: OP) -> R
+    where
+        OP: FnOnce() -> R + Send,
+        R: Send,
+    {
+        match self {
+            Parallelism::Serial | Parallelism::RayonDefaultPool { .. } => op(),
+            Parallelism::RayonNewPool(num_threads) => {
+                let mut thread_pool = ThreadPoolBuilder::new();
+                if *num_threads > 0 {
+                    thread_pool = thread_pool.num_threads(*num_threads);
+                }
+                match thread_pool.build() {
+                    Ok(thread_pool) => thread_pool.install(op),
+                    Err(_) => op(),
+                }
+            }
+            Parallelism::RayonExistingPool { pool, .. } => pool.install(op),
+        }
+    }
+
     pub(crate) fn timeout(&self) -> Option<std::time::Duration> {
         match self {
             Parallelism::Serial | Parallelism::RayonNewPool(_) => None,
@@ -608,11 +1521,49 @@ impl Parallelism {
     }
 }
 
-fn is_hidden(file_name: &OsStr) -> bool {
-    file_name
-        .to_str()
-        .map(|s| s.starts_with('.'))
-        .unwrap_or(false)
+fn mark_duplicate_inode<C: ClientState>(
+    dir_entry: &mut DirEntry<C>,
+    seen_inodes: &Mutex<HashSet<(u64, u64)>>,
+) {
+    // Symlinks which are not followed don't share the identity of their target.
+    if dir_entry.file_type.is_symlink() {
+        return;
+    }
+    let metadata_ext = match dir_entry.metadata_ext.as_ref() {
+        Some(metadata_ext) => metadata_ext,
+        None => return,
+    };
+    // Files with a single link can't be reached twice, don't grow the set.
+    if !dir_entry.file_type.is_dir() && metadata_ext.link_count() == Some(1) {
+        return;
+    }
+    if let Some(inode_key) = metadata_ext.inode_key() {
+        if !seen_inodes.lock().unwrap().insert(inode_key) {
+            dir_entry.duplicate_inode = true;
+            dir_entry.read_children_path = None;
+        }
+    }
+}
+
+fn dedupe_roots(roots: Vec<PathBuf>) -> Vec<PathBuf> {
+    let canonical: Vec<PathBuf> = roots
+        .iter()
+        .map(|root| fs::canonicalize(root).unwrap_or_else(|_| root.clone()))
+        .collect();
+    roots
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| {
+            !canonical.iter().enumerate().any(|(j, other)| {
+                if canonical[*i] == *other {
+                    j < *i
+                } else {
+                    canonical[*i].starts_with(other)
+                }
+            })
+        })
+        .map(|(_, root)| root)
+        .collect()
 }
 
 impl<B, E> ClientState for (B, E)
@@ -0,0 +1,255 @@
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+// Only the directory helpers are used here
+#[allow(dead_code)]
+mod util;
+
+use util::Dir;
+
+fn run(program: &str, args: &[&str], cwd: &Path) -> String {
+    let output = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{} {:?} failed: {}",
+        program,
+        args,
+        String::from_utf8_lossy(&output.stderr)
+    );
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn du(args: &[&str], cwd: &Path) -> String {
+    run(env!("CARGO_BIN_EXE_jwalk-du"), args, cwd)
+}
+
+#[test]
+fn du_apparent_size_per_dir() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root/a/b");
+    dir.mkdirp("root/c");
+    fs::write(dir.join("root/a/f1"), vec![0; 5000]).unwrap();
+    fs::write(dir.join("root/a/b/f2"), vec![0; 3000]).unwrap();
+    fs::write(dir.join("root/c/.hidden"), vec![0; 100]).unwrap();
+    let size = |path: &str| fs::metadata(dir.join(path)).unwrap().len();
+    let (root, a, b, c) = (
+        size("root"),
+        size("root/a"),
+        size("root/a/b"),
+        size("root/c"),
+    );
+
+    let stdout = du(&["--apparent-size", "--json", "root"], dir.path());
+    let expected = format!(
+        "[\n  {{\"path\": \"root/a/b\", \"size\": {}, \"depth\": 2}},\n  \
+         {{\"path\": \"root/a\", \"size\": {}, \"depth\": 1}},\n  \
+         {{\"path\": \"root/c\", \"size\": {}, \"depth\": 1}},\n  \
+         {{\"path\": \"root\", \"size\": {}, \"depth\": 0}}\n]\n",
+        3000 + b,
+        8000 + a + b,
+        100 + c,
+        8100 + root + a + b + c,
+    );
+    // Siblings come in read_dir order, compare without separators
+    let mut lines: Vec<&str> = stdout.lines().map(|l| l.trim_end_matches(',')).collect();
+    let mut expected_lines: Vec<&str> = expected.lines().map(|l| l.trim_end_matches(',')).collect();
+    lines.sort_unstable();
+    expected_lines.sort_unstable();
+    assert_eq!(lines, expected_lines);
+
+    let stdout = du(
+        &["--apparent-size", "-s", "--exclude", "f?", "root"],
+        dir.path(),
+    );
+    assert_eq!(
+        stdout,
+        format!("{}\troot\n", (100 + root + a + b + c).div_ceil(1024))
+    );
+}
+
+#[test]
+fn du_hard_links_and_top() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root/a");
+    dir.mkdirp("root/b");
+    fs::write(dir.join("root/a/big"), vec![0; 100_000]).unwrap();
+    fs::hard_link(dir.join("root/a/big"), dir.join("root/b/big")).unwrap();
+
+    let total = |args: &[&str]| -> u64 {
+        let stdout = du(args, dir.path());
+        let line = stdout.lines().last().unwrap();
+        line.split('\t').next().unwrap().parse().unwrap()
+    };
+    let deduped = total(&["--apparent-size", "root"]);
+    let counted = total(&["--apparent-size", "-l", "root"]);
+    assert!(deduped < 200, "{}", deduped);
+    assert!(counted >= 195, "{}", counted);
+
+    let stdout = du(&["--apparent-size", "-l", "--top", "2", "root"], dir.path());
+    let paths: Vec<&str> = stdout
+        .lines()
+        .map(|line| line.split('\t').nth(1).unwrap())
+        .collect();
+    assert_eq!(paths[0], "root");
+    assert_eq!(paths.len(), 2);
+}
+
+#[test]
+fn du_hard_links_with_exclude_and_several_paths() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root/sub");
+    dir.mkdirp("other");
+    fs::write(dir.join("root/sub/big"), vec![0; 100_000]).unwrap();
+    // Read before root/sub, the excluded link must not hide the kept one
+    fs::hard_link(dir.join("root/sub/big"), dir.join("root/big.bak")).unwrap();
+    fs::hard_link(dir.join("root/sub/big"), dir.join("other/big")).unwrap();
+
+    let totals = |args: &[&str]| -> Vec<(u64, String)> {
+        du(args, dir.path())
+            .lines()
+            .map(|line| {
+                let (size, path) = line.split_once('\t').unwrap();
+                (size.parse().unwrap(), path.to_owned())
+            })
+            .collect()
+    };
+    let lines = totals(&[
+        "--apparent-size",
+        "-j",
+        "1",
+        "--exclude",
+        "*.bak",
+        "-s",
+        "root",
+    ]);
+    assert!(lines[0].0 >= 98, "{:?}", lines);
+
+    // Shared between PATHs, the file is counted for the first one only
+    let lines = totals(&["--apparent-size", "-s", "root", "other"]);
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].0 >= 98, "{:?}", lines);
+    assert!(lines[1].0 < 10, "{:?}", lines);
+
+    // A PATH inside another PATH isn't counted again
+    let lines = totals(&["--apparent-size", "-s", "root", "root/sub"]);
+    assert_eq!(lines.len(), 1, "{:?}", lines);
+}
+
+fn jwalk(args: &[&str], cwd: &Path) -> String {
+    run(env!("CARGO_BIN_EXE_jwalk"), args, cwd)
+}
+
+#[test]
+fn find_prune_and_tests() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root/a/skip/deep");
+    dir.mkdirp("root/b");
+    dir.touch_all(&["root/a/x.txt", "root/a/skip/deep/y.txt", "root/.hidden.txt"]);
+    fs::write(dir.join("root/b/big.bin"), vec![0; 3000]).unwrap();
+
+    let stdout = jwalk(&["root", "-sort"], dir.path());
+    assert_eq!(stdout.lines().count(), 9);
+
+    let stdout = jwalk(
+        &[
+            "root", "-sort", "-path", "*/skip", "-prune", "-o", "-type", "f", "-print",
+        ],
+        dir.path(),
+    );
+    assert_eq!(stdout, "root/.hidden.txt\nroot/a/x.txt\nroot/b/big.bin\n");
+
+    let stdout = jwalk(
+        &[
+            "root", "-sort", "-name", "*.txt", "!", "-name", ".*", "-print0",
+        ],
+        dir.path(),
+    );
+    assert_eq!(stdout, "root/a/skip/deep/y.txt\0root/a/x.txt\0");
+
+    let stdout = jwalk(&["root", "-type", "f", "-size", "+2k"], dir.path());
+    assert_eq!(stdout, "root/b/big.bin\n");
+
+    let stdout = jwalk(
+        &[
+            "root",
+            "-sort",
+            "-mindepth",
+            "1",
+            "-maxdepth",
+            "1",
+            "-empty",
+        ],
+        dir.path(),
+    );
+    assert_eq!(stdout, "root/.hidden.txt\n");
+}
+
+#[cfg(unix)]
+#[test]
+fn find_exec_batches() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root");
+    let names: Vec<String> = (0..10).map(|i| format!("root/{}.txt", i)).collect();
+    dir.touch_all(&names);
+
+    let stdout = jwalk(
+        &["root", "-sort", "-type", "f", "-exec", "echo", "{}", "+"],
+        dir.path(),
+    );
+    let expected: Vec<&str> = names.iter().map(String::as_str).collect();
+    assert_eq!(stdout, format!("{}\n", expected.join(" ")));
+
+    let stdout = jwalk(
+        &[
+            "root", "-sort", "-name", "1.*", "-exec", "echo", "x{}x", ";",
+        ],
+        dir.path(),
+    );
+    assert_eq!(stdout, "xroot/1.txtx\n");
+}
+
+#[cfg(any(feature = "blake3", feature = "sha2", feature = "xxh3"))]
+#[test]
+fn duplicates_json() {
+    let dir = Dir::tmp();
+    dir.mkdirp("root/b");
+    let contents: Vec<u8> = (0..200 * 1024).map(|i| (i % 251) as u8).collect();
+    fs::write(dir.join("root/a"), &contents).unwrap();
+    fs::write(dir.join("root/b/c"), &contents).unwrap();
+    let mut changed = contents.clone();
+    changed[100 * 1024] ^= 1;
+    fs::write(dir.join("root/d"), &changed).unwrap();
+    fs::write(dir.join("root/e"), b"hello").unwrap();
+    fs::write(dir.join("root/b/f"), b"hello").unwrap();
+    dir.touch_all(&["root/empty1", "root/empty2"]);
+
+    let stdout = jwalk(&["duplicates", "--json", "root"], dir.path());
+    let sets: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    let sets: Vec<(u64, Vec<&str>)> = sets
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|set| {
+            let paths = set["paths"].as_array().unwrap();
+            (
+                set["size"].as_u64().unwrap(),
+                paths.iter().map(|path| path.as_str().unwrap()).collect(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        sets,
+        vec![
+            (200 * 1024, vec!["root/a", "root/b/c"]),
+            (5, vec!["root/b/f", "root/e"]),
+        ]
+    );
+
+    let stdout = jwalk(&["duplicates", "--min-size=6", "root"], dir.path());
+    assert_eq!(stdout, "root/a\nroot/b/c\n\n");
+}
@@ -594,6 +594,48 @@ fn sym_loop_detect() {
     assert!(err.io_error().is_none());
 }
 
+#[cfg(unix)]
+#[test]
+fn sym_loop_detect_relative_target() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b/c");
+    std::os::unix::fs::symlink("../..", dir.join("a/b/c/up")).unwrap();
+
+    let wd = WalkDir::new(dir.path()).follow_links(true);
+    let r = dir.run_recursive(wd);
+
+    let (ents, errs) = (r.ents(), r.errs());
+    assert_eq!(4, ents.len());
+    assert_eq!(1, errs.len());
+
+    let err = &errs[0];
+    assert_eq!(Some(&*dir.join("a/b/c/up")), err.path());
+    assert_eq!(Some(&*dir.join("a")), err.loop_ancestor());
+    assert_eq!(4, err.depth());
+    assert!(err.io_error().is_none());
+}
+
+#[test]
+fn sym_loop_detect_multi_hop() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b/c");
+    dir.symlink_dir("l2", "a/b/c/l1");
+    dir.symlink_dir("a", "l2");
+
+    let wd = WalkDir::new(dir.join("a")).follow_links(true);
+    let r = dir.run_recursive(wd);
+
+    let (ents, errs) = (r.ents(), r.errs());
+    assert_eq!(3, ents.len());
+    assert_eq!(1, errs.len());
+
+    let err = &errs[0];
+    assert_eq!(Some(&*dir.join("a/b/c/l1")), err.path());
+    assert_eq!(Some(&*dir.join("a")), err.loop_ancestor());
+    assert_eq!(3, err.depth());
+    assert!(err.io_error().is_none());
+}
+
 #[test]
 fn sym_self_loop_no_error() {
     let dir = Dir::tmp();
@@ -830,6 +872,7 @@ fn walk_serial() {
             "group 1 (1)",
             "group 1/d.txt (2)",
             "group 2 (1)",
+            "group 2/.hidden_file.txt (2)",
             "group 2/e.txt (2)",
         ]
     );
@@ -853,6 +896,7 @@ fn sort_by_name_rayon_custom_2_threads() {
             "group 1 (1)",
             "group 1/d.txt (2)",
             "group 2 (1)",
+            "group 2/.hidden_file.txt (2)",
             "group 2/e.txt (2)",
         ]
     );
@@ -872,6 +916,7 @@ fn walk_rayon_global() {
             "group 1 (1)",
             "group 1/d.txt (2)",
             "group 2 (1)",
+            "group 2/.hidden_file.txt (2)",
             "group 2/e.txt (2)",
         ]
     );
@@ -1043,6 +1088,7 @@ fn walk_relative_1() {
             "group 1 (1)",
             "group 1/d.txt (2)",
             "group 2 (1)",
+            "group 2/.hidden_file.txt (2)",
             "group 2/e.txt (2)",
         ]
     );
@@ -1056,7 +1102,7 @@ fn walk_relative_2() {
     let _shared = RELATIVE_MUTEX.lock().unwrap();
     let (test_dir, _temp_dir) = test_dir();
 
-    env::set_current_dir(&test_dir.join("group 1")).unwrap();
+    env::set_current_dir(test_dir.join("group 1")).unwrap();
 
     let paths = local_paths(WalkDir::new("..").sort(true));
 
@@ -1070,6 +1116,7 @@ fn walk_relative_2() {
             "group 1 (1)",
             "group 1/d.txt (2)",
             "group 2 (1)",
+            "group 2/.hidden_file.txt (2)",
             "group 2/e.txt (2)",
         ]
     );
@@ -1140,3 +1187,1295 @@ fn test_read_linux() {
         }
     }
 }
+
+#[test]
+fn retry_policy_transient_errors() {
+    let policy = RetryPolicy::transient(3, std::time::Duration::ZERO);
+    assert!(policy.is_transient(&std::io::Error::from(std::io::ErrorKind::Interrupted)));
+    assert!(policy.is_transient(&std::io::Error::from(std::io::ErrorKind::WouldBlock)));
+    assert!(!policy.is_transient(&std::io::Error::from(std::io::ErrorKind::NotFound)));
+    assert!(!RetryPolicy::default()
+        .is_transient(&std::io::Error::from(std::io::ErrorKind::Interrupted)));
+}
+
+#[test]
+fn retry_read_dir_until_directory_reappears() {
+    let (test_dir, _temp_dir) = test_dir();
+    let walk_dir = WalkDir::new(&test_dir)
+        .parallelism(Parallelism::Serial)
+        .retry_policy(RetryPolicy {
+            max_attempts: 20,
+            backoff: std::time::Duration::from_millis(10),
+            kinds: vec![std::io::ErrorKind::NotFound],
+            raw_os_errors: Vec::new(),
+        })
+        .sort(true);
+    let mut iter = walk_dir.into_iter();
+
+    // Read root. read_dir for root is also called since single thread mode.
+    let root = iter.next().unwrap().unwrap();
+    assert_eq!(root.retries, 0);
+
+    // Remove group 2 dir from disk and recreate it while the walk is retrying.
+    let group_2_path = test_dir.join("group 2");
+    fs::remove_dir_all(&group_2_path).unwrap();
+    let recreate = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        fs::create_dir(&group_2_path).unwrap();
+        fs::write(group_2_path.join("f.txt"), "").unwrap();
+    });
+
+    let names: Vec<_> = iter
+        .map(|each| {
+            let each = each.unwrap();
+            assert!(each.read_children_error.is_none());
+            (each.file_name.to_string_lossy().to_string(), each.retries)
+        })
+        .collect();
+    recreate.join().unwrap();
+
+    let group_2 = names.iter().find(|(name, _)| name == "group 2").unwrap();
+    assert!(group_2.1 > 0);
+    assert!(names.iter().any(|(name, _)| name == "f.txt"));
+}
+
+#[test]
+fn multi_root_grouped_in_order() {
+    let dir = Dir::tmp();
+    dir.mkdirp("b/x");
+    dir.mkdirp("a/y");
+    dir.touch_all(&["b/x/1", "a/y/2", "c"]);
+
+    for parallelism in [Parallelism::Serial, Parallelism::RayonNewPool(2)] {
+        let wd = WalkDir::new_multi([dir.join("b"), dir.join("c"), dir.join("a")])
+            .parallelism(parallelism)
+            .sort(true);
+        let r = dir.run_recursive(wd);
+        r.assert_no_errors();
+
+        let expected = vec![
+            dir.join("b"),
+            dir.join("b/x"),
+            dir.join("b/x/1"),
+            dir.join("c"),
+            dir.join("a"),
+            dir.join("a/y"),
+            dir.join("a/y/2"),
+        ];
+        assert_eq!(expected, r.paths());
+    }
+}
+
+#[test]
+fn multi_root_dedupe_overlap() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.mkdirp("c");
+    dir.touch_all(&["a/b/1", "c/2"]);
+
+    let roots = [dir.join("a/b"), dir.join("c"), dir.join("a"), dir.join("c")];
+
+    let wd = WalkDir::new_multi(&roots).sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    assert_eq!(9, r.ents().len());
+
+    let wd = WalkDir::new_multi(&roots).dedupe_roots(true).sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    let expected = vec![
+        dir.join("c"),
+        dir.join("c/2"),
+        dir.join("a"),
+        dir.join("a/b"),
+        dir.join("a/b/1"),
+    ];
+    assert_eq!(expected, r.paths());
+}
+
+#[test]
+fn dedupe_inodes_hard_links() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a");
+    dir.mkdirp("b");
+    dir.touch_all(&["a/x", "a/single"]);
+    fs::hard_link(dir.join("a/x"), dir.join("b/y")).unwrap();
+
+    let wd = WalkDir::new(dir.path()).sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    assert!(r.ents().iter().all(|ent| !ent.duplicate_inode));
+
+    let wd = WalkDir::new(dir.path()).dedupe_inodes(true).sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    assert_eq!(6, r.ents().len());
+    let duplicates: Vec<_> = r
+        .ents()
+        .iter()
+        .filter(|ent| ent.duplicate_inode)
+        .map(|ent| ent.path())
+        .collect();
+    assert_eq!(1, duplicates.len());
+    assert!(duplicates[0] == dir.join("a/x") || duplicates[0] == dir.join("b/y"));
+}
+
+#[test]
+fn dedupe_inodes_follow_links() {
+    let dir = Dir::tmp();
+    dir.mkdirp("d");
+    dir.touch("d/f");
+    dir.symlink_dir("d", "l1");
+    dir.symlink_dir("d", "l2");
+
+    let wd = WalkDir::new(dir.path()).follow_links(true).sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    assert_eq!(7, r.ents().len());
+
+    let wd = WalkDir::new(dir.path())
+        .follow_links(true)
+        .dedupe_inodes(true)
+        .sort(true);
+    let r = dir.run_recursive(wd);
+    r.assert_no_errors();
+    let duplicates = r.ents().iter().filter(|ent| ent.duplicate_inode).count();
+    let files = r.ents().iter().filter(|ent| ent.file_name() == "f").count();
+    assert_eq!(2, duplicates);
+    assert_eq!(1, files);
+}
+
+#[test]
+fn fold_dirs_sizes_bottom_up() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.mkdirp("c");
+    fs::write(dir.join("a/1"), "1").unwrap();
+    fs::write(dir.join("a/b/2"), "22").unwrap();
+    fs::write(dir.join("a/b/3"), "333").unwrap();
+    fs::write(dir.join("c/4"), "4444").unwrap();
+    fs::write(dir.join("5"), "55555").unwrap();
+
+    for parallelism in [Parallelism::Serial, Parallelism::RayonNewPool(2)] {
+        let results: Vec<_> = WalkDir::new(dir.path())
+            .parallelism(parallelism)
+            .sort(true)
+            .read_metadata(true)
+            .fold_dirs(
+                || (0u64, 0usize),
+                |(size, files), entry| {
+                    if entry.file_type().is_file() {
+                        *size += entry.metadata.as_ref().unwrap().size;
+                        *files += 1;
+                    }
+                },
+                |(size, files), (child_size, child_files)| {
+                    *size += child_size;
+                    *files += child_files;
+                },
+            )
+            .map(|result| {
+                let (dir_entry, value) = result.unwrap();
+                (dir_entry.path(), value)
+            })
+            .collect();
+
+        assert_eq!(
+            results,
+            vec![
+                (dir.join("a/b"), (5, 2)),
+                (dir.join("a"), (6, 3)),
+                (dir.join("c"), (4, 1)),
+                (dir.path().to_path_buf(), (15, 5)),
+            ]
+        );
+    }
+}
+
+#[test]
+fn fold_dirs_with_entry_predicate() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    fs::write(dir.join("a/1"), "1").unwrap();
+    fs::write(dir.join("a/b/2"), "22").unwrap();
+    fs::write(dir.join("a/b/3"), "333").unwrap();
+    fs::write(dir.join("4"), "4444").unwrap();
+
+    for parallelism in [Parallelism::Serial, Parallelism::RayonNewPool(2)] {
+        // Directories don't match, they are still folded and yielded but
+        // not passed to per_entry
+        let results: Vec<_> = WalkDir::new(dir.path())
+            .parallelism(parallelism)
+            .sort(true)
+            .read_metadata(true)
+            .file_types(&[EntryType::File])
+            .fold_dirs(
+                || (0u64, 0usize),
+                |(size, files), entry| {
+                    assert!(entry.file_type().is_file());
+                    *size += entry.metadata.as_ref().unwrap().size;
+                    *files += 1;
+                },
+                |(size, files), (child_size, child_files)| {
+                    *size += child_size;
+                    *files += child_files;
+                },
+            )
+            .map(|result| {
+                let (dir_entry, value) = result.unwrap();
+                (dir_entry.path(), value)
+            })
+            .collect();
+
+        assert_eq!(
+            results,
+            vec![
+                (dir.join("a/b"), (5, 2)),
+                (dir.join("a"), (6, 3)),
+                (dir.path().to_path_buf(), (10, 4)),
+            ]
+        );
+    }
+}
+
+#[test]
+fn tree_lines_and_totals() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.mkdirp("c");
+    fs::write(dir.join("a/1"), "1").unwrap();
+    fs::write(dir.join("a/b/2"), "22").unwrap();
+    fs::write(dir.join("c/3"), "333").unwrap();
+    fs::write(dir.join("4"), "4444").unwrap();
+    let root = dir.path().display().to_string();
+
+    let lines: Vec<String> = WalkDir::new(dir.path())
+        .sort(true)
+        .tree(TreeOptions::default())
+        .map(|line| line.unwrap())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            root.as_str(),
+            "├── 4",
+            "├── a",
+            "│   ├── 1",
+            "│   └── b",
+            "│       └── 2",
+            "└── c",
+            "    └── 3",
+            "",
+            "3 directories, 4 files",
+        ]
+    );
+
+    let options = TreeOptions {
+        sizes: true,
+        dir_totals: true,
+        max_depth: 1,
+        report: false,
+        ..TreeOptions::default()
+    };
+    let lines: Vec<String> = WalkDir::new(dir.path())
+        .parallelism(Parallelism::RayonNewPool(2))
+        .sort(true)
+        .tree(options)
+        .map(|line| line.unwrap())
+        .collect();
+    assert_eq!(
+        lines,
+        vec![
+            root.clone(),
+            "├── [         4]  4".to_owned(),
+            "├── a  (2 files, 3 bytes)".to_owned(),
+            "└── c  (1 file, 3 bytes)".to_owned(),
+            "(4 files, 10 bytes)".to_owned(),
+        ]
+    );
+}
+
+#[test]
+fn tree_with_error_and_filtered_entries() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a");
+    dir.touch("a/1");
+    dir.symlink_dir("a", "a/z-link");
+    let root = dir.path().display().to_string();
+
+    // The loop error sorts last in `a`, `1` is still its last child
+    let mut errors = 0;
+    let lines: Vec<String> = WalkDir::new(dir.path())
+        .follow_links(true)
+        .sort(true)
+        .tree(TreeOptions::default())
+        .filter_map(|line| {
+            errors += line.is_err() as usize;
+            line.ok()
+        })
+        .collect();
+    assert_eq!(errors, 1);
+    assert_eq!(
+        lines,
+        vec![
+            root.as_str(),
+            "└── a",
+            "    └── 1",
+            "",
+            "1 directory, 1 file",
+        ]
+    );
+
+    // Directories not matching the predicate are held back, they aren't
+    // siblings of the yielded files
+    dir.mkdirp("b");
+    dir.touch("b/2");
+    dir.touch("0");
+    let entries: Vec<(PathBuf, usize, usize, bool)> = WalkDir::new(dir.path())
+        .sort(true)
+        .file_types(&[EntryType::File])
+        .into_iter()
+        .map(|dir_entry| {
+            let dir_entry = dir_entry.unwrap();
+            (
+                dir_entry.path(),
+                dir_entry.index_in_parent(),
+                dir_entry.sibling_count(),
+                dir_entry.is_last_sibling(),
+            )
+        })
+        .collect();
+    assert_eq!(
+        entries,
+        vec![
+            (dir.join("0"), 0, 1, true),
+            (dir.join("a/1"), 0, 1, true),
+            (dir.join("b/2"), 0, 1, true),
+        ]
+    );
+}
+
+#[test]
+fn sibling_position_and_entry_id() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.touch_all(&["a/1", "a/2", "c"]);
+
+    let walk = |parallelism| -> Vec<(PathBuf, usize, usize, bool, u64)> {
+        WalkDir::new(dir.path())
+            .parallelism(parallelism)
+            .sort(true)
+            .into_iter()
+            .map(|dir_entry| {
+                let dir_entry = dir_entry.unwrap();
+                (
+                    dir_entry.path(),
+                    dir_entry.index_in_parent(),
+                    dir_entry.sibling_count(),
+                    dir_entry.is_last_sibling(),
+                    dir_entry.entry_id(),
+                )
+            })
+            .collect()
+    };
+
+    let serial = walk(Parallelism::Serial);
+    let positions: Vec<_> = serial
+        .iter()
+        .map(|(path, index, count, last, _)| (path.clone(), *index, *count, *last))
+        .collect();
+    assert_eq!(
+        positions,
+        vec![
+            (dir.path().to_path_buf(), 0, 1, true),
+            (dir.join("a"), 0, 2, false),
+            (dir.join("a/1"), 0, 3, false),
+            (dir.join("a/2"), 1, 3, false),
+            (dir.join("a/b"), 2, 3, true),
+            (dir.join("c"), 1, 2, true),
+        ]
+    );
+
+    let mut ids: Vec<u64> = serial.iter().map(|entry| entry.4).collect();
+    assert_eq!(serial, walk(Parallelism::RayonNewPool(2)));
+    ids.sort_unstable();
+    ids.dedup();
+    assert_eq!(ids.len(), serial.len());
+}
+
+#[test]
+fn resume_from_checkpoint() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b/c");
+    dir.mkdirp("d");
+    dir.mkdirp("r2/x");
+    dir.touch_all(&[
+        "a/1", "a/b/2", "a/b/c/3", "a/z", "d/4", "5", "r2/x/6", "r2/7",
+    ]);
+    let roots = [dir.join("a"), dir.join("d"), dir.join("r2")];
+
+    let full: Vec<PathBuf> = WalkDir::new_multi(&roots)
+        .sort(true)
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap().path())
+        .collect();
+    assert!(WalkDir::new_multi(&roots)
+        .into_iter()
+        .checkpoint()
+        .is_none());
+
+    for parallelism in [Parallelism::Serial, Parallelism::RayonNewPool(2)] {
+        for taken in 1..=full.len() {
+            let mut iter = WalkDir::new_multi(&roots)
+                .parallelism(parallelism.clone())
+                .sort(true)
+                .into_iter();
+            let mut paths: Vec<PathBuf> = iter
+                .by_ref()
+                .take(taken)
+                .map(|dir_entry| dir_entry.unwrap().path())
+                .collect();
+            let checkpoint = iter.checkpoint().unwrap();
+            drop(iter);
+
+            paths.extend(
+                WalkDir::new_multi(&roots)
+                    .parallelism(parallelism.clone())
+                    .resume_from(checkpoint)
+                    .into_iter()
+                    .map(|dir_entry| dir_entry.unwrap().path()),
+            );
+            assert_eq!(paths, full, "resumed after {} entries", taken);
+        }
+    }
+}
+
+#[test]
+fn resume_from_checkpoint_after_changes() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.touch_all(&["a/b/1", "a/b/3", "a/c"]);
+
+    let mut iter = WalkDir::new(dir.path()).sort(true).into_iter();
+    let last = iter.by_ref().take(4).last().unwrap().unwrap();
+    assert_eq!(last.path(), dir.join("a/b/1"));
+    let checkpoint = iter.checkpoint().unwrap();
+    drop(iter);
+    assert_eq!(checkpoint.root, 0);
+    assert_eq!(checkpoint.names, vec!["a", "b", "1"]);
+
+    // Entries sorting before the checkpoint are finished, later ones are not
+    dir.touch_all(&["a/b/0", "a/b/2", "a/0"]);
+    let paths: Vec<PathBuf> = WalkDir::new(dir.path())
+        .resume_from(checkpoint)
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap().path())
+        .collect();
+    assert_eq!(
+        paths,
+        vec![dir.join("a/b/2"), dir.join("a/b/3"), dir.join("a/c")]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn checkpoint_serde_roundtrip() {
+    let checkpoint = Checkpoint {
+        root: 1,
+        names: vec!["dir".into(), "file".into()],
+    };
+    let json = serde_json::to_string(&checkpoint).unwrap();
+    assert_eq!(
+        serde_json::from_str::<Checkpoint>(&json).unwrap(),
+        checkpoint
+    );
+}
+
+#[test]
+fn par_iter_unordered() {
+    let dir = Dir::tmp();
+    for i in 0..10 {
+        dir.mkdirp(format!("d{}/e", i));
+        dir.touch_all(&[format!("d{}/f", i), format!("d{}/e/g", i)]);
+    }
+
+    let mut expected: Vec<(PathBuf, u64)> = WalkDir::new(dir.path())
+        .min_depth(1)
+        .into_iter()
+        .map(|dir_entry| {
+            let dir_entry = dir_entry.unwrap();
+            (dir_entry.path(), dir_entry.entry_id())
+        })
+        .collect();
+    expected.sort();
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let mut paths: Vec<(PathBuf, u64)> = pool.install(|| {
+        WalkDir::new(dir.path())
+            .min_depth(1)
+            .into_par_iter()
+            .map(|dir_entry| {
+                let dir_entry = dir_entry.unwrap();
+                (dir_entry.path(), dir_entry.entry_id())
+            })
+            .collect()
+    });
+    paths.sort();
+    assert_eq!(expected.len(), 40);
+    assert_eq!(paths, expected);
+
+    let errors: Vec<_> = WalkDir::new(dir.join("missing")).into_par_iter().collect();
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].is_err());
+}
+
+#[test]
+fn process_entry_in_parallel() {
+    let dir = Dir::tmp();
+    dir.mkdirp("big");
+    for i in 0..200 {
+        let name = format!("big/{:03}", i);
+        dir.touch(&name);
+        fs::write(dir.join(&name), vec![0u8; i]).unwrap();
+    }
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(4)
+        .build()
+        .unwrap();
+    let entries: Vec<(String, usize)> = WalkDirGeneric::<((), usize)>::new(dir.join("big"))
+        .sort(true)
+        .min_depth(1)
+        .parallelism(Parallelism::RayonExistingPool {
+            pool: pool.into(),
+            busy_timeout: None,
+        })
+        .process_entry_chunk_size(8)
+        .process_entry(|dir_entry| {
+            if dir_entry.file_type().is_file() {
+                dir_entry.client_state = fs::read(dir_entry.path()).unwrap().len() + 1;
+            }
+        })
+        .into_iter()
+        .map(|dir_entry| {
+            let dir_entry = dir_entry.unwrap();
+            (
+                dir_entry.file_name().to_string_lossy().into_owned(),
+                dir_entry.client_state,
+            )
+        })
+        .collect();
+
+    let expected: Vec<(String, usize)> = (0..200).map(|i| (format!("{:03}", i), i + 1)).collect();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn large_dir_processed_in_parallel() {
+    let dir = Dir::tmp();
+    for i in 0..500 {
+        dir.touch(format!("{}", i * 7919 % 500));
+    }
+    dir.touch(".hidden");
+
+    let walk = |threshold: usize, sort: bool| -> Vec<(PathBuf, bool)> {
+        WalkDir::new(dir.path())
+            .sort(sort)
+            .skip_hidden(true)
+            .read_metadata(true)
+            .large_dir_threshold(threshold)
+            .into_iter()
+            .map(|dir_entry| {
+                let dir_entry = dir_entry.unwrap();
+                (dir_entry.path(), dir_entry.metadata.is_some())
+            })
+            .collect()
+    };
+
+    let sorted = walk(1, true);
+    assert_eq!(sorted.len(), 501);
+    assert!(sorted.iter().all(|(_, has_metadata)| *has_metadata));
+    assert_eq!(sorted, walk(usize::MAX, true));
+    assert_eq!(walk(1, false), walk(usize::MAX, false));
+}
+
+#[test]
+fn metadata_matches_std() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.touch_all(&["a/f", "a/b/g"]);
+    fs::write(dir.join("a/f"), b"contents").unwrap();
+    #[cfg(unix)]
+    dir.symlink_file(dir.join("a/f"), dir.join("a/link"));
+
+    for dir_entry in WalkDir::new(dir.path())
+        .read_metadata(true)
+        .read_metadata_ext(true)
+    {
+        let dir_entry = dir_entry.unwrap();
+        let path = dir_entry.path();
+        let expected = fs::symlink_metadata(&path).unwrap();
+        let metadata = dir_entry.metadata.as_ref().unwrap();
+        assert_eq!(metadata.is_dir, expected.is_dir(), "{:?}", path);
+        assert_eq!(metadata.is_symlink, expected.is_symlink(), "{:?}", path);
+        assert_eq!(metadata.size, expected.len(), "{:?}", path);
+        assert_eq!(metadata.modified, expected.modified().ok(), "{:?}", path);
+        assert_eq!(
+            metadata.permissions.as_ref(),
+            Some(&expected.permissions()),
+            "{:?}",
+            path
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            let expected = fs::metadata(&path).unwrap();
+            let metadata_ext = dir_entry.metadata_ext.as_ref().unwrap();
+            assert_eq!(metadata_ext.st_ino, expected.ino(), "{:?}", path);
+            assert_eq!(metadata_ext.st_dev, expected.dev(), "{:?}", path);
+            assert_eq!(metadata_ext.st_mode, expected.mode(), "{:?}", path);
+            assert_eq!(metadata_ext.st_nlink, expected.nlink(), "{:?}", path);
+            assert_eq!(metadata_ext.st_blocks, expected.blocks(), "{:?}", path);
+        }
+    }
+}
+
+#[cfg(feature = "blake3")]
+#[test]
+fn content_hash_files() {
+    let dir = Dir::tmp();
+    dir.mkdirp("sub");
+    fs::write(dir.join("a"), b"hello").unwrap();
+    fs::write(dir.join("sub/b"), b"hello").unwrap();
+    let mut large = vec![1u8; 10 * 1024];
+    fs::write(dir.join("large1"), &large).unwrap();
+    large[5 * 1024] = 2;
+    fs::write(dir.join("large2"), &large).unwrap();
+    fs::write(dir.join("huge"), vec![0u8; 64 * 1024]).unwrap();
+
+    let hashes = |partial_kib: Option<u64>| {
+        let content_hash = ContentHash {
+            max_size: Some(32 * 1024),
+            partial_kib,
+            ..ContentHash::new(HashAlgorithm::Blake3)
+        };
+        WalkDir::new(dir.path())
+            .sort(true)
+            .content_hash(content_hash)
+            .into_iter()
+            .map(|dir_entry| {
+                let dir_entry = dir_entry.unwrap();
+                assert!(dir_entry.content_hash_error.is_none());
+                (
+                    dir_entry.file_name().to_string_lossy().into_owned(),
+                    dir_entry.content_hash,
+                )
+            })
+            .collect::<std::collections::HashMap<_, _>>()
+    };
+
+    let full = hashes(None);
+    let hello = Some(blake3::hash(b"hello").as_bytes().to_vec());
+    assert_eq!(full["a"], hello);
+    assert_eq!(full["b"], hello);
+    assert_eq!(full["sub"], None);
+    assert_eq!(full["huge"], None);
+    assert_ne!(full["large1"], full["large2"]);
+
+    let partial = hashes(Some(4));
+    assert_eq!(partial["a"], hello);
+    assert_eq!(partial["large1"], partial["large2"]);
+    assert_ne!(partial["large1"], full["large1"]);
+}
+
+#[cfg(all(unix, feature = "xxh3"))]
+#[test]
+fn find_duplicates_skips_hard_links() {
+    use jwalk_meta::duplicates::{find_duplicates, DuplicateOptions};
+
+    let dir = Dir::tmp();
+    dir.mkdirp("x");
+    dir.mkdirp("y");
+    fs::write(dir.join("x/a"), vec![7u8; 3000]).unwrap();
+    fs::hard_link(dir.join("x/a"), dir.join("x/link")).unwrap();
+    fs::write(dir.join("y/b"), vec![7u8; 3000]).unwrap();
+    fs::write(dir.join("y/other"), vec![8u8; 3000]).unwrap();
+    fs::write(dir.join("y/single"), vec![7u8; 10]).unwrap();
+    fs::write(dir.join("only_link"), b"unique").unwrap();
+    fs::hard_link(dir.join("only_link"), dir.join("only_link2")).unwrap();
+
+    let options = DuplicateOptions {
+        partial_kib: 1,
+        ..DuplicateOptions::new(HashAlgorithm::Xxh3)
+    };
+    let duplicates = find_duplicates(
+        WalkDir::new_multi([dir.join("x"), dir.join("y"), dir.join("only_link")]),
+        &options,
+    );
+    assert!(duplicates.errors.is_empty());
+    assert_eq!(duplicates.sets.len(), 1);
+    let set = &duplicates.sets[0];
+    assert_eq!(set.size, 3000);
+    assert_eq!(set.paths.len(), 2);
+    assert!(set.paths[0] == dir.join("x/a") || set.paths[0] == dir.join("x/link"));
+    assert_eq!(set.paths[1], dir.join("y/b"));
+
+    let duplicates = find_duplicates(WalkDir::new(dir.path()), &options);
+    assert_eq!(duplicates.sets.len(), 1);
+}
+
+#[test]
+fn skip_hidden_predicates() {
+    let dir = Dir::tmp();
+    dir.mkdirp(".root/.git/objects");
+    dir.mkdirp(".root/src");
+    dir.touch_all(&[".root/.env", ".root/src/main.rs", ".root/src/main.rs~"]);
+    let root = dir.join(".root");
+
+    let names = |walk_dir: WalkDir| -> Vec<String> {
+        walk_dir
+            .sort(true)
+            .into_iter()
+            .map(|dir_entry| {
+                let path = dir_entry.unwrap().path();
+                let path = path.strip_prefix(&root).unwrap();
+                path.to_string_lossy().into_owned()
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        names(WalkDir::new(&root)),
+        vec![
+            "",
+            ".env",
+            ".git",
+            ".git/objects",
+            "src",
+            "src/main.rs",
+            "src/main.rs~"
+        ]
+    );
+    let visible = vec!["", "src", "src/main.rs", "src/main.rs~"];
+    assert_eq!(names(WalkDir::new(&root).skip_hidden(true)), visible);
+    assert_eq!(
+        names(WalkDir::new(&root).skip_hidden_by(Hidden::Dotfiles)),
+        visible
+    );
+    #[cfg(not(windows))]
+    assert_eq!(
+        names(WalkDir::new(&root).skip_hidden_by(Hidden::Both)),
+        visible
+    );
+    #[cfg(not(windows))]
+    assert_eq!(
+        names(WalkDir::new(&root).skip_hidden_by(Hidden::OsAttribute)).len(),
+        7
+    );
+    let backups = Hidden::Custom(std::sync::Arc::new(|name| {
+        name.to_string_lossy().ends_with('~')
+    }));
+    assert_eq!(
+        names(WalkDir::new(&root).skip_hidden_by(backups)),
+        vec!["", ".env", ".git", ".git/objects", "src", "src/main.rs"]
+    );
+}
+
+#[test]
+fn filter_name_before_construction() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/node_modules/pkg");
+    dir.mkdirp("a/b/c/d");
+    dir.touch_all(&[
+        "a/x.o",
+        "a/x.c",
+        "a/node_modules/pkg/index.js",
+        "a/b/c/d/deep.c",
+    ]);
+
+    let seen = std::sync::Arc::new(Mutex::new(Vec::new()));
+    let seen_by_filter = seen.clone();
+    let paths: Vec<PathBuf> = WalkDir::new(dir.join("a"))
+        .sort(true)
+        .read_metadata(true)
+        .filter_name(move |file_name, file_type, depth| {
+            seen_by_filter
+                .lock()
+                .unwrap()
+                .push((file_name.to_string_lossy().into_owned(), depth));
+            if file_type.is_dir() {
+                file_name != "node_modules" && depth < 3
+            } else {
+                !file_name.to_string_lossy().ends_with(".o")
+            }
+        })
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap().path())
+        .collect();
+
+    assert_eq!(
+        paths,
+        vec![
+            dir.join("a"),
+            dir.join("a/b"),
+            dir.join("a/b/c"),
+            dir.join("a/x.c"),
+        ]
+    );
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort();
+    assert_eq!(
+        seen,
+        vec![
+            ("b".to_owned(), 1),
+            ("c".to_owned(), 2),
+            ("d".to_owned(), 3),
+            ("node_modules".to_owned(), 1),
+            ("x.c".to_owned(), 1),
+            ("x.o".to_owned(), 1),
+        ]
+    );
+}
+
+#[test]
+fn skip_current_dir_and_children() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/sub");
+    dir.mkdirp("c/d");
+    dir.touch_all(&[
+        "a/1.txt",
+        "a/2.txt",
+        "a/sub/x.txt",
+        "c/1.txt",
+        "c/2.txt",
+        "c/d/x.txt",
+    ]);
+    dir.touch("d.txt");
+    for i in 0..20 {
+        dir.mkdirp(format!("b/d{}/e", i));
+        dir.touch(format!("b/d{}/e/f.txt", i));
+    }
+
+    for parallelism in [Parallelism::Serial, Parallelism::RayonNewPool(4)] {
+        let mut iter = WalkDir::new(dir.path())
+            .sort(true)
+            .parallelism(parallelism)
+            .into_iter();
+        let mut paths = Vec::new();
+        while let Some(dir_entry) = iter.next() {
+            let path = dir_entry.unwrap().path();
+            let relative = path.strip_prefix(dir.path()).unwrap().to_path_buf();
+            if relative.as_os_str() == "b" {
+                iter.skip_entry_children();
+            } else if relative.as_os_str() == "c/1.txt" {
+                iter.skip_current_dir();
+            } else if relative.as_os_str() == "d.txt" {
+                // Not a directory, nothing to skip
+                iter.skip_entry_children();
+            }
+            paths.push(relative);
+        }
+
+        assert_eq!(
+            paths,
+            vec![
+                PathBuf::new(),
+                PathBuf::from("a"),
+                PathBuf::from("a/1.txt"),
+                PathBuf::from("a/2.txt"),
+                PathBuf::from("a/sub"),
+                PathBuf::from("a/sub/x.txt"),
+                PathBuf::from("b"),
+                PathBuf::from("c"),
+                PathBuf::from("c/1.txt"),
+                PathBuf::from("d.txt"),
+            ]
+        );
+    }
+}
+
+#[test]
+fn entry_predicates() {
+    use std::time::{Duration, SystemTime};
+
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.touch_all(&["empty.txt", "a/b/empty.txt"]);
+    fs::write(dir.join("small.txt"), "0123").unwrap();
+    fs::write(dir.join("a/large.txt"), "0123456789").unwrap();
+    fs::write(dir.join("a/b/large.txt"), "0123456789").unwrap();
+    dir.symlink_file(dir.join("small.txt"), "link.txt");
+
+    let walk = |walk_dir: WalkDir| -> Vec<PathBuf> {
+        walk_dir
+            .sort(true)
+            .into_iter()
+            .map(|dir_entry| {
+                let path = dir_entry.unwrap().path();
+                path.strip_prefix(dir.path()).unwrap().to_path_buf()
+            })
+            .collect()
+    };
+
+    // Directories which don't match are still descended
+    assert_eq!(
+        walk(
+            WalkDir::new(dir.path())
+                .file_types(&[EntryType::File])
+                .min_size(4)
+        ),
+        vec![
+            PathBuf::from("a/b/large.txt"),
+            PathBuf::from("a/large.txt"),
+            PathBuf::from("small.txt"),
+        ]
+    );
+    assert_eq!(
+        walk(
+            WalkDir::new(dir.path())
+                .file_types(&[EntryType::File])
+                .max_size(4)
+        ),
+        vec![
+            PathBuf::from("a/b/empty.txt"),
+            PathBuf::from("empty.txt"),
+            PathBuf::from("small.txt"),
+        ]
+    );
+    assert_eq!(
+        walk(WalkDir::new(dir.path()).file_types(&[EntryType::Symlink, EntryType::Dir])),
+        vec![
+            PathBuf::new(),
+            PathBuf::from("a"),
+            PathBuf::from("a/b"),
+            PathBuf::from("link.txt"),
+        ]
+    );
+
+    let old = SystemTime::now() - Duration::from_secs(3600);
+    fs::File::options()
+        .write(true)
+        .open(dir.join("a/large.txt"))
+        .unwrap()
+        .set_modified(old)
+        .unwrap();
+    assert_eq!(
+        walk(
+            WalkDir::new(dir.path())
+                .file_types(&[EntryType::File])
+                .modified_before(old + Duration::from_secs(60))
+        ),
+        vec![PathBuf::from("a/large.txt")]
+    );
+    assert_eq!(
+        walk(
+            WalkDir::new(dir.path())
+                .file_types(&[EntryType::File, EntryType::Symlink])
+                .min_size(1)
+                .modified_after(old + Duration::from_secs(60))
+        ),
+        vec![
+            PathBuf::from("a/b/large.txt"),
+            PathBuf::from("link.txt"),
+            PathBuf::from("small.txt"),
+        ]
+    );
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+        fs::set_permissions(dir.join("small.txt"), fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(
+            walk(
+                WalkDir::new(dir.path())
+                    .file_types(&[EntryType::File])
+                    .permission_mask(0o111)
+            ),
+            vec![PathBuf::from("small.txt")]
+        );
+
+        let uid = fs::metadata(dir.path()).unwrap().uid();
+        assert_eq!(
+            walk(WalkDir::new(dir.path()).owned_by(uid)).len(),
+            walk(WalkDir::new(dir.path())).len()
+        );
+        assert!(walk(WalkDir::new(dir.path()).owned_by(uid.wrapping_add(1))).is_empty());
+    }
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn match_and_exclude_regex() {
+    use jwalk_meta::regex::bytes::RegexSet;
+
+    let dir = Dir::tmp();
+    dir.mkdirp("src/core");
+    dir.mkdirp("target/debug");
+    dir.mkdirp("docs");
+    dir.touch_all(&[
+        "src/main.rs",
+        "src/secret.pem",
+        "src/core/key.pem",
+        "target/debug/x.pem",
+        "docs/readme.md",
+    ]);
+
+    let walk = |walk_dir: WalkDir| -> Vec<PathBuf> {
+        walk_dir
+            .sort(true)
+            .into_iter()
+            .map(|dir_entry| {
+                let path = dir_entry.unwrap().path();
+                path.strip_prefix(dir.path()).unwrap().to_path_buf()
+            })
+            .collect()
+    };
+
+    let pem = RegexSet::new([r"\.pem$", r"^id_rsa$"]).unwrap();
+    assert_eq!(
+        walk(WalkDir::new(dir.path()).match_regex(pem.clone(), RegexTarget::FileName)),
+        vec![
+            PathBuf::new(),
+            PathBuf::from("src/core/key.pem"),
+            PathBuf::from("src/secret.pem"),
+            PathBuf::from("target/debug/x.pem"),
+        ]
+    );
+    assert_eq!(
+        walk(
+            WalkDir::new(dir.path())
+                .match_regex(pem, RegexTarget::FileName)
+                .exclude_regex(
+                    RegexSet::new([r"^target$"]).unwrap(),
+                    RegexTarget::RelativePath
+                )
+        ),
+        vec![
+            PathBuf::new(),
+            PathBuf::from("src/core/key.pem"),
+            PathBuf::from("src/secret.pem"),
+        ]
+    );
+    assert_eq!(
+        walk(WalkDir::new(dir.path()).match_regex(
+            RegexSet::new([r"^src/core/", r"^docs$"]).unwrap(),
+            RegexTarget::RelativePath
+        )),
+        vec![
+            PathBuf::new(),
+            PathBuf::from("docs"),
+            PathBuf::from("src/core/key.pem"),
+        ]
+    );
+    assert_eq!(
+        walk(WalkDir::new(dir.path()).exclude_regex(
+            RegexSet::new([r"\.(rs|md)$", "debug"]).unwrap(),
+            RegexTarget::FileName
+        )),
+        vec![
+            PathBuf::new(),
+            PathBuf::from("docs"),
+            PathBuf::from("src"),
+            PathBuf::from("src/core"),
+            PathBuf::from("src/core/key.pem"),
+            PathBuf::from("src/secret.pem"),
+            PathBuf::from("target"),
+        ]
+    );
+}
+
+#[test]
+fn relative_path_and_root_style() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a/b");
+    dir.touch_all(&["a/b/c.txt", "a/d.txt"]);
+    let root = dir.join("a/b/..");
+
+    let expected = vec![
+        PathBuf::new(),
+        PathBuf::from("b"),
+        PathBuf::from("b/c.txt"),
+        PathBuf::from("d.txt"),
+    ];
+    let entries: Vec<_> = WalkDir::new(&root)
+        .sort(true)
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap())
+        .collect();
+    let relative_paths: Vec<_> = entries.iter().map(|e| e.relative_path()).collect();
+    assert_eq!(relative_paths, expected);
+    for dir_entry in &entries {
+        assert_eq!(dir_entry.path(), root.join(dir_entry.relative_path()));
+    }
+
+    let stripped: Vec<_> = WalkDir::new(&root)
+        .sort(true)
+        .root_style(RootStyle::Stripped)
+        .into_iter()
+        .map(|dir_entry| {
+            let dir_entry = dir_entry.unwrap();
+            assert!(dir_entry.parent_path().starts_with(root.parent().unwrap()));
+            dir_entry.path()
+        })
+        .collect();
+    assert_eq!(stripped, expected);
+
+    let canonical_root = fs::canonicalize(dir.join("a")).unwrap();
+    let canonical: Vec<_> = WalkDir::new(&root)
+        .sort(true)
+        .root_style(RootStyle::Canonical)
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap().path())
+        .collect();
+    assert_eq!(
+        canonical,
+        expected
+            .iter()
+            .map(|relative_path| canonical_root.join(relative_path))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn file_name_storage() {
+    use std::ffi::{OsStr, OsString};
+
+    assert!(std::mem::size_of::<FileName>() <= std::mem::size_of::<OsString>());
+
+    let short = FileName::from("short.txt");
+    let long_name = "a file name longer than the inline capacity.txt";
+    let long = FileName::from(OsString::from(long_name));
+    assert_eq!(short, "short.txt");
+    assert_eq!(long.as_os_str(), OsStr::new(long_name));
+    assert!(long < short);
+    assert_eq!(OsString::from(long.clone()), OsString::from(long_name));
+
+    let dir = Dir::tmp();
+    dir.touch_all(&["short.txt", long_name]);
+    let file_names: Vec<_> = WalkDir::new(dir.path())
+        .sort(true)
+        .min_depth(1)
+        .into_iter()
+        .map(|dir_entry| dir_entry.unwrap().file_name)
+        .collect();
+    assert_eq!(file_names, vec![long, short]);
+}
+
+#[test]
+fn parallel_order_with_many_subdirectories() {
+    let dir = Dir::tmp();
+    // Directory indices above 255 take multi byte index paths
+    for i in 0..300 {
+        dir.mkdirp(format!("wide/{:03}/sub", i));
+        dir.touch(format!("wide/{:03}/sub/file.txt", i));
+    }
+    let mut deep = PathBuf::from("deep");
+    for i in 0..40 {
+        deep.push(format!("level{}", i));
+    }
+    dir.mkdirp(&deep);
+    dir.touch(deep.join("file.txt"));
+
+    let walk = |parallelism| -> Vec<PathBuf> {
+        WalkDir::new(dir.path())
+            .sort(true)
+            .parallelism(parallelism)
+            .into_iter()
+            .map(|dir_entry| dir_entry.unwrap().path())
+            .collect()
+    };
+    let serial = walk(Parallelism::Serial);
+    assert_eq!(serial.len(), 1 + 1 + 300 * 3 + 1 + 40 + 1);
+    assert_eq!(walk(Parallelism::RayonNewPool(4)), serial);
+}
+
+#[test]
+fn relaxed_ordering() {
+    let dir = Dir::tmp();
+    dir.mkdirp("a_slow/sub");
+    dir.touch("a_slow/sub/file.txt");
+    for name in ["b", "c", "d"] {
+        for sub in ["x", "y"] {
+            dir.mkdirp(format!("{}/{}", name, sub));
+            dir.touch(format!("{}/{}/1.txt", name, sub));
+            dir.touch(format!("{}/{}/2.txt", name, sub));
+        }
+    }
+
+    let walk = |ordering| {
+        WalkDir::new(dir.path())
+            .sort(true)
+            .parallelism(Parallelism::RayonNewPool(4))
+            .ordering(ordering)
+            .process_read_dir(|_, path, _, _| {
+                if path.ends_with("a_slow") {
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+            })
+    };
+    let entries = |walk_dir: WalkDir| -> Vec<(PathBuf, u64)> {
+        walk_dir
+            .into_iter()
+            .map(|dir_entry| {
+                let dir_entry = dir_entry.unwrap();
+                (dir_entry.relative_path(), dir_entry.entry_id())
+            })
+            .collect()
+    };
+    let strict = entries(walk(ResultOrdering::Strict));
+    let relaxed = entries(walk(ResultOrdering::Relaxed));
+
+    // Same entries with the same ids
+    let mut sorted = relaxed.clone();
+    sorted.sort();
+    let mut strict_sorted = strict.clone();
+    strict_sorted.sort();
+    assert_eq!(sorted, strict_sorted);
+
+    // The slow directory doesn't hold back the others
+    let position = |path: &str| relaxed.iter().position(|(p, _)| p == &PathBuf::from(path));
+    assert!(position("b/x/1.txt").unwrap() < position("a_slow/sub").unwrap());
+
+    // Parents come first, the entries of a directory follow each other sorted
+    for (i, (path, _)) in relaxed.iter().enumerate().skip(1) {
+        assert!(position(path.parent().unwrap().to_str().unwrap()).unwrap() < i);
+        let siblings: Vec<_> = relaxed
+            .iter()
+            .enumerate()
+            .filter(|(_, (p, _))| p.parent() == path.parent())
+            .collect();
+        let first = siblings[0].0;
+        for (j, (k, (p, _))) in siblings.iter().enumerate() {
+            assert_eq!(*k, first + j);
+            if j > 0 {
+                assert!(siblings[j - 1].1 .0 < *p);
+            }
+        }
+    }
+
+    // Skipping a directory drops its results and those of its descendants
+    let mut iter = walk(ResultOrdering::Relaxed).into_iter();
+    let mut paths = Vec::new();
+    while let Some(dir_entry) = iter.next() {
+        let dir_entry = dir_entry.unwrap();
+        let relative_path = dir_entry.relative_path();
+        if relative_path.as_os_str() == "c" {
+            iter.skip_entry_children();
+        } else if relative_path.as_os_str() == "d/x/1.txt" {
+            iter.skip_current_dir();
+        }
+        paths.push(relative_path);
+    }
+    assert!(iter.checkpoint().is_none());
+    assert!(paths.contains(&PathBuf::from("c")));
+    assert!(!paths
+        .iter()
+        .any(|path| path.parent().is_some_and(|parent| parent.starts_with("c"))));
+    assert!(!paths.contains(&PathBuf::from("d/x/2.txt")));
+    assert!(paths.contains(&PathBuf::from("d/y/2.txt")));
+    assert_eq!(paths.len(), strict.len() - 6 - 1);
+}